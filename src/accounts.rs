@@ -1,7 +1,7 @@
 use crate::config::Config;
-use bs58;
-use solana_client::rpc_client::RpcClient;
+use crate::endpoint::redact_client_error;
 use solana_sdk::{
+    commitment_config::CommitmentConfig,
     pubkey::Pubkey,
     signature::{read_keypair_file, Keypair, Signer},
 };
@@ -45,18 +45,10 @@ impl AccountInfo {
     }
 }
 
-pub async fn determine_account_roles(
-    config: &Config,
-) -> Result<(AccountInfo, AccountInfo), Box<dyn Error>> {
-    if config.rpc_urls.is_empty() {
-        return Err("No RPC URLs provided in configuration.".into());
-    }
-    let rpc_url = &config.rpc_urls[0];
-    println!("Using RPC URL for balance check: {}", rpc_url);
-    let rpc_client = RpcClient::new(rpc_url.to_string());
-
+/// Loads both configured keypairs, in configuration order, without fetching balances.
+pub fn load_accounts(config: &Config) -> Result<(AccountInfo, AccountInfo), Box<dyn Error>> {
     let keypair_path_1_expanded = config.keypair_path_1_expanded()?;
-    let mut account1 = AccountInfo::new_from_path(&keypair_path_1_expanded)?;
+    let account1 = AccountInfo::new_from_path(&keypair_path_1_expanded)?;
     println!(
         "Loaded account 1 from '{}' with pubkey: {}",
         keypair_path_1_expanded.display(),
@@ -64,19 +56,41 @@ pub async fn determine_account_roles(
     );
 
     let keypair_path_2_expanded = config.keypair_path_2_expanded()?;
-    let mut account2 = AccountInfo::new_from_path(&keypair_path_2_expanded)?;
+    let account2 = AccountInfo::new_from_path(&keypair_path_2_expanded)?;
     println!(
         "Loaded account 2 from '{}' with pubkey: {}",
         keypair_path_2_expanded.display(),
         bs58::encode(account2.pubkey.to_bytes()).into_string()
     );
 
+    Ok((account1, account2))
+}
+
+pub async fn determine_account_roles(
+    config: &Config,
+) -> Result<(AccountInfo, AccountInfo), Box<dyn Error>> {
+    let endpoints = config.enabled_endpoints();
+    let endpoint = endpoints
+        .first()
+        .ok_or("No enabled endpoints provided in configuration.")?;
+    println!("Using RPC for balance check: {}", endpoint.name);
+    // Confirmed commitment so that back-to-back races see the previous race's transfer.
+    let rpc_client = endpoint.nonblocking_rpc_client(CommitmentConfig::confirmed());
+
+    let (mut account1, mut account2) = load_accounts(config)?;
+
     println!("Fetching balance for account 1 ({})...", account1.pubkey);
-    let balance1 = rpc_client.get_balance(&account1.pubkey)?;
+    let balance1 = rpc_client
+        .get_balance(&account1.pubkey)
+        .await
+        .map_err(redact_client_error)?;
     println!("Balance for account 1: {} lamports", balance1);
 
     println!("Fetching balance for account 2 ({})...", account2.pubkey);
-    let balance2 = rpc_client.get_balance(&account2.pubkey)?;
+    let balance2 = rpc_client
+        .get_balance(&account2.pubkey)
+        .await
+        .map_err(redact_client_error)?;
     println!("Balance for account 2: {} lamports", balance2);
 
     let (sender_account, recipient_account) = if balance1 >= balance2 {
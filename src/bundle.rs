@@ -0,0 +1,398 @@
+use crate::accounts::AccountInfo;
+use crate::endpoint::{build_header_map, redact_url, Endpoint};
+use crate::fees::ComputeBudgetConfig;
+use crate::send_path::{
+    warm_up_http, SendPath, SendPathConfig, Submission, Submit, WireTransaction,
+};
+use crate::transactions::SendAttempt;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_client::client_error::reqwest::{
+    header::{HeaderMap, HeaderValue, CONTENT_TYPE},
+    Client as HttpClient,
+};
+use solana_sdk::{
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signer,
+    system_instruction,
+    transaction::{Transaction, VersionedTransaction},
+};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    time::{Duration, Instant},
+};
+
+fn default_tip_lamports() -> u64 {
+    1000
+}
+
+/// Settings of a block-engine path that submits the race transaction in a bundle.
+///
+/// The bundle holds the race transaction followed by a transfer of
+/// `tip_lamports` from the sender to `tip_account`.
+#[derive(Deserialize, Clone, PartialEq, Eq)]
+pub struct BundlePathConfig {
+    /// Block-engine bundle endpoint, e.g. `https://mainnet.block-engine.jito.wtf/api/v1/bundles`.
+    pub url: String,
+    /// Extra headers, e.g. an authentication token.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(deserialize_with = "crate::config::deserialize_pubkey")]
+    pub tip_account: Pubkey,
+    #[serde(default = "default_tip_lamports")]
+    pub tip_lamports: u64,
+}
+
+impl BundlePathConfig {
+    pub fn header_map(&self, endpoint_name: &str) -> Result<HeaderMap, String> {
+        let mut base = HeaderMap::new();
+        base.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        build_header_map(endpoint_name, base, &self.headers)
+    }
+
+    fn http_client(&self, endpoint: &Endpoint) -> Result<HttpClient, String> {
+        HttpClient::builder()
+            .default_headers(self.header_map(&endpoint.name)?)
+            .timeout(endpoint.timeout())
+            .build()
+            .map_err(|e| format!("Endpoint '{}': {}", endpoint.name, e))
+    }
+
+    /// Lamports the sender needs for the tip transaction, on top of the race transaction.
+    pub fn tip_cost_lamports(&self) -> u64 {
+        self.tip_lamports + ComputeBudgetConfig::default().total_fee_lamports(1, 1)
+    }
+}
+
+impl fmt::Debug for BundlePathConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BundlePathConfig")
+            .field("url", &redact_url(&self.url))
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .field("tip_account", &self.tip_account)
+            .field("tip_lamports", &self.tip_lamports)
+            .finish()
+    }
+}
+
+/// Signs the tip transfer that accompanies a race transaction in its bundle.
+pub fn construct_tip_transaction(
+    sender: &AccountInfo,
+    config: &BundlePathConfig,
+    blockhash: Hash,
+) -> VersionedTransaction {
+    let instruction =
+        system_instruction::transfer(&sender.pubkey, &config.tip_account, config.tip_lamports);
+    Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&sender.keypair.pubkey()),
+        &[&sender.keypair],
+        blockhash,
+    )
+    .into()
+}
+
+/// Makes one JSON-RPC call to the block engine and returns its `result`.
+async fn call(
+    client: &HttpClient,
+    url: &str,
+    method: &str,
+    params: Value,
+) -> Result<Value, String> {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    // Strip the URL from errors, as it may carry an API key.
+    let response = client
+        .post(url)
+        .body(request.to_string())
+        .send()
+        .await
+        .map_err(|e| e.without_url().to_string())?;
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .map_err(|e| e.without_url().to_string())?;
+    if !status.is_success() {
+        return Err(format!(
+            "Block engine returned HTTP {}: {}",
+            status,
+            text.chars().take(200).collect::<String>()
+        ));
+    }
+    let mut body: Value =
+        serde_json::from_str(&text).map_err(|e| format!("Invalid block engine response: {}", e))?;
+    if let Some(error) = body.get("error") {
+        return Err(format!("Block engine error: {}", error));
+    }
+    Ok(body["result"].take())
+}
+
+/// Submits race transactions as `sendBundle` calls to a block engine.
+pub struct BundlePath {
+    client: HttpClient,
+    url: String,
+}
+
+impl BundlePath {
+    pub fn new(endpoint: &Endpoint, config: &BundlePathConfig) -> Result<Self, String> {
+        Ok(BundlePath {
+            client: config.http_client(endpoint)?,
+            url: config.url.clone(),
+        })
+    }
+}
+
+impl SendPath for BundlePath {
+    /// Succeeds once the block engine accepted the bundle; whether it lands is
+    /// up to monitoring.
+    fn submit<'a>(&'a self, transaction: &'a WireTransaction) -> Submit<'a> {
+        Box::pin(async move {
+            let tip = transaction
+                .tip
+                .as_ref()
+                .ok_or("Bundle path requires a tip transaction")?;
+            let params = json!([
+                [
+                    BASE64_STANDARD.encode(&transaction.bytes),
+                    BASE64_STANDARD.encode(tip)
+                ],
+                { "encoding": "base64" }
+            ]);
+            let result = call(&self.client, &self.url, "sendBundle", params).await?;
+            let bundle_id = result
+                .as_str()
+                .ok_or_else(|| format!("Block engine returned no bundle ID: {}", result))?;
+            Ok(Submission {
+                signature: transaction.signature,
+                bundle_id: Some(bundle_id.to_string()),
+            })
+        })
+    }
+
+    fn warm_up(&self) -> Option<Result<(), String>> {
+        Some(warm_up_http(&self.client, &self.url))
+    }
+}
+
+/// Describes one entry of a `getBundleStatuses` result, or `None` if the
+/// block engine does not know the bundle (yet).
+fn describe_bundle_status(status: &Value) -> Option<String> {
+    if status.is_null() {
+        return None;
+    }
+    let confirmation = status["confirmation_status"].as_str().unwrap_or("unknown");
+    let mut description = match status["slot"].as_u64() {
+        Some(slot) => format!("Landed ({}) in slot {}", confirmation, slot),
+        None => format!("Landed ({})", confirmation),
+    };
+    match status.get("err") {
+        Some(err) if !err.is_null() && err.get("Ok").is_none() => {
+            description.push_str(&format!(", error: {}", err));
+        }
+        _ => {}
+    }
+    Some(description)
+}
+
+/// Polls `getBundleStatuses` for every accepted bundle until the block engine
+/// reports it or `timeout` passes, and records the result on the attempt.
+pub async fn poll_bundle_statuses(
+    attempts: &mut [SendAttempt],
+    timeout: Duration,
+    poll_interval: Duration,
+) {
+    let deadline = Instant::now() + timeout;
+    for attempt in attempts.iter_mut() {
+        let (SendPathConfig::Bundle(config), Some(bundle_id)) =
+            (&attempt.endpoint.send_path, attempt.bundle_id.clone())
+        else {
+            continue;
+        };
+        let config = config.clone();
+        let client = match config.http_client(&attempt.endpoint) {
+            Ok(client) => client,
+            Err(e) => {
+                attempt.bundle_status = Some(format!("Status check failed: {}", e));
+                continue;
+            }
+        };
+        let status = loop {
+            match call(
+                &client,
+                &config.url,
+                "getBundleStatuses",
+                json!([[bundle_id]]),
+            )
+            .await
+            {
+                Ok(result) => {
+                    if let Some(description) = result["value"]
+                        .as_array()
+                        .and_then(|statuses| statuses.first())
+                        .and_then(describe_bundle_status)
+                    {
+                        break description;
+                    }
+                }
+                Err(e) => break format!("Status check failed: {}", e),
+            }
+            if Instant::now() >= deadline {
+                break "Not reported by the block engine".to_string();
+            }
+            tokio::time::sleep(poll_interval).await;
+        };
+        println!(
+            "Bundle {} from RPC {}: {}",
+            bundle_id, attempt.endpoint.name, status
+        );
+        attempt.bundle_status = Some(status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::send_path::mock_http;
+    use solana_sdk::signature::{Keypair, Signature};
+
+    fn bundle_path(url: String) -> BundlePath {
+        let endpoint =
+            Endpoint::from_url("bundle".to_string(), "http://localhost:8899".to_string());
+        BundlePath::new(
+            &endpoint,
+            &BundlePathConfig {
+                url,
+                headers: BTreeMap::new(),
+                tip_account: Pubkey::new_unique(),
+                tip_lamports: 1000,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tip_transaction_transfers_tip_from_sender() {
+        let keypair = Keypair::new();
+        let sender = AccountInfo {
+            pubkey: keypair.pubkey(),
+            keypair,
+            balance: 1_000_000,
+            role: None,
+        };
+        let config = BundlePathConfig {
+            url: "http://localhost".to_string(),
+            headers: BTreeMap::new(),
+            tip_account: Pubkey::new_unique(),
+            tip_lamports: 1234,
+        };
+
+        let transaction = construct_tip_transaction(&sender, &config, Hash::new_unique());
+
+        let keys = transaction.message.static_account_keys();
+        assert_eq!(keys[0], sender.pubkey);
+        assert!(keys.contains(&config.tip_account));
+        assert!(transaction.verify_with_results().iter().all(|ok| *ok));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bundle_path_sends_transaction_and_tip() {
+        let (url, requests) = mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            r#"{"jsonrpc":"2.0","result":"bundle-1","id":1}"#.to_string(),
+        )]);
+        let transaction = WireTransaction {
+            bytes: vec![1, 2, 3],
+            signature: Signature::default(),
+            tip: Some(vec![4, 5, 6]),
+        };
+
+        let outcome = bundle_path(url).send(&transaction);
+
+        assert_eq!(outcome.result, Ok(Signature::default()));
+        assert_eq!(outcome.bundle_id.as_deref(), Some("bundle-1"));
+        let request: Value = serde_json::from_str(&requests.recv().unwrap()).unwrap();
+        assert_eq!(request["method"], "sendBundle");
+        assert_eq!(request["params"][0], json!(["AQID", "BAUG"]));
+        assert_eq!(request["params"][1]["encoding"], "base64");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bundle_path_reports_block_engine_error() {
+        let (url, _requests) = mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"bundle rejected"},"id":1}"#
+                .to_string(),
+        )]);
+        let transaction = WireTransaction {
+            bytes: vec![1, 2, 3],
+            signature: Signature::default(),
+            tip: Some(vec![4, 5, 6]),
+        };
+
+        let outcome = bundle_path(url).send(&transaction);
+
+        assert!(outcome.result.unwrap_err().contains("bundle rejected"));
+        assert_eq!(outcome.bundle_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_poll_bundle_statuses_waits_for_block_engine() {
+        let (url, requests) = mock_http::serve(vec![
+            (
+                "HTTP/1.1 200 OK",
+                r#"{"jsonrpc":"2.0","result":{"context":{"slot":41},"value":[null]},"id":1}"#
+                    .to_string(),
+            ),
+            (
+                "HTTP/1.1 200 OK",
+                r#"{"jsonrpc":"2.0","result":{"context":{"slot":43},"value":[{"bundle_id":"bundle-1","slot":42,"confirmation_status":"confirmed","err":{"Ok":null}}]},"id":1}"#
+                    .to_string(),
+            ),
+        ]);
+        let mut endpoint =
+            Endpoint::from_url("bundle".to_string(), "http://localhost:8899".to_string());
+        endpoint.send_path = SendPathConfig::Bundle(BundlePathConfig {
+            url,
+            headers: BTreeMap::new(),
+            tip_account: Pubkey::new_unique(),
+            tip_lamports: 1000,
+        });
+        let mut attempts = vec![SendAttempt {
+            bundle_id: Some("bundle-1".to_string()),
+            ..SendAttempt::accepted(endpoint)
+        }];
+
+        poll_bundle_statuses(
+            &mut attempts,
+            Duration::from_secs(5),
+            Duration::from_millis(10),
+        )
+        .await;
+
+        assert_eq!(
+            attempts[0].bundle_status.as_deref(),
+            Some("Landed (confirmed) in slot 42")
+        );
+        let request: Value = serde_json::from_str(&requests.recv().unwrap()).unwrap();
+        assert_eq!(request["method"], "getBundleStatuses");
+        assert_eq!(request["params"], json!([["bundle-1"]]));
+    }
+
+    #[test]
+    fn test_describe_bundle_status() {
+        assert_eq!(describe_bundle_status(&Value::Null), None);
+        assert_eq!(
+            describe_bundle_status(&json!({
+                "bundle_id": "bundle-1",
+                "slot": 42,
+                "confirmation_status": "confirmed",
+                "err": { "Ok": null }
+            }))
+            .as_deref(),
+            Some("Landed (confirmed) in slot 42")
+        );
+    }
+}
@@ -1,17 +1,143 @@
-use clap::Parser;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand};
 
 /// Usopp-Send: A tool to test Solana RPC node transaction propagation speed.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct CliArgs {
     /// Path to the configuration file.
-    #[arg(short, long, default_value = "config.json")]
+    #[arg(short, long, default_value = "config.json", global = true)]
     pub config_path: String,
 
-    /// Enable dry-run mode.
-    /// In dry-run mode, transactions are constructed and simulated but not sent to the network.
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Send conflicting transactions through every endpoint and report which confirms first.
+    Race(RaceArgs),
+    /// Construct and simulate the race transactions without sending them to the network.
+    Simulate,
+    /// Print the balances of both keypairs as seen by every enabled endpoint.
+    Balance,
+    /// Move all funds from one keypair into the other.
+    Sweep(SweepArgs),
+    /// Re-render the report of a stored race.
+    Report(ReportArgs),
+    /// List stored races and per-RPC latency percentiles and win rates.
+    History(HistoryArgs),
+    /// Create and manage the durable nonce account used by the `durable_nonce` strategy.
+    #[command(subcommand)]
+    Nonce(NonceCommand),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum NonceCommand {
+    /// Create a new nonce account, funded by and with its authority set to the given keypair.
+    Create(NonceCreateArgs),
+    /// Print the configured nonce account's authority, stored nonce and balance.
+    Show,
+    /// Advance the configured nonce account, invalidating transactions signed with its nonce.
+    Advance,
+    /// Withdraw all lamports of the configured nonce account to its authority, closing it.
+    Close,
+}
+
+#[derive(Args, Debug)]
+pub struct NonceCreateArgs {
+    /// Keypair (1 or 2) that funds the account and becomes its authority.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    pub authority: u8,
+}
+
+impl Command {
+    /// Whether the command talks to the configured endpoints, as opposed to only reading history.
+    pub fn uses_endpoints(&self) -> bool {
+        !matches!(self, Command::Report(_) | Command::History(_))
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct RaceArgs {
+    /// Number of races to run back to back.
+    /// Balances and the blockhash are re-fetched before every race, and an
+    /// aggregated per-RPC report is printed once more than one race was run.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub rounds: u32,
+
+    /// Also print the DNS, connect, TLS, write and first-byte timing of every send.
     #[arg(long)]
-    pub dry_run: bool,
+    pub timings: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct SweepArgs {
+    /// Keypair (1 or 2) that receives the funds. Defaults to the one with the higher balance.
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    pub into: Option<u8>,
+}
+
+#[derive(Args, Debug)]
+pub struct ReportArgs {
+    /// Id of the stored race, as shown by `history`. Defaults to the most recent race.
+    pub race_id: Option<u64>,
+
+    /// Also print the DNS, connect, TLS, write and first-byte timing of every send.
+    #[arg(long)]
+    pub timings: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    /// Only include races this endpoint took part in. May be repeated.
+    #[arg(long = "endpoint")]
+    pub endpoints: Vec<String>,
+
+    /// Only include races started at or after this time (`YYYY-MM-DD` or RFC 3339).
+    #[arg(long, value_parser = parse_since)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only include races started before this time. A bare date includes that whole day.
+    #[arg(long, value_parser = parse_until)]
+    pub until: Option<DateTime<Utc>>,
+
+    /// Maximum number of most recent races to list individually.
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+
+    /// Also include races flagged as unfair because of their dispatch skew.
+    #[arg(long)]
+    pub include_unfair: bool,
+}
+
+fn parse_date(value: &str) -> Result<(DateTime<Utc>, bool), String> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Ok((date_time.with_timezone(&Utc), false));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| {
+            (
+                date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc(),
+                true,
+            )
+        })
+        .map_err(|_| format!("'{}' is neither YYYY-MM-DD nor RFC 3339", value))
+}
+
+fn parse_since(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_date(value).map(|(date_time, _)| date_time)
+}
+
+fn parse_until(value: &str) -> Result<DateTime<Utc>, String> {
+    let (date_time, date_only) = parse_date(value)?;
+    if date_only {
+        date_time
+            .checked_add_days(Days::new(1))
+            .ok_or_else(|| format!("'{}' is out of range", value))
+    } else {
+        Ok(date_time)
+    }
 }
 
 impl CliArgs {
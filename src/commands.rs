@@ -0,0 +1,473 @@
+use crate::accounts::{determine_account_roles, load_accounts};
+use crate::cli::{HistoryArgs, NonceCommand, RaceArgs, ReportArgs, SweepArgs};
+use crate::config::Config;
+use crate::endpoint::redact_client_error;
+use crate::history::{append_race, load_races, HistoryFilter, RaceRecord};
+use crate::nonce::{
+    construct_advance_nonce_transaction, construct_close_nonce_transaction,
+    construct_create_nonce_transaction, fetch_nonce, nonce_authority,
+};
+use crate::race::{prepare_race, run_live_race};
+use crate::report::{
+    generate_aggregate_table, generate_balance_table, generate_history_table,
+    generate_percentile_table, generate_position_table, print_race_report, BalanceRow,
+};
+use crate::stats::{aggregate_endpoint_stats, aggregate_position_stats};
+use crate::strategy::ConflictStrategyConfig;
+use crate::transactions::{construct_sweep_transaction, simulate_transactions_concurrently};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::{read_keypair_file, Keypair, Signer},
+};
+use std::{error::Error, process::ExitCode};
+
+/// Runs `args.rounds` live races, persisting each one, then prints the aggregate report.
+pub async fn run_race(conf: &Config, args: &RaceArgs) -> ExitCode {
+    let history_path = match conf.history_path_expanded() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Error: Invalid history path: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let config_fingerprint = conf.fingerprint();
+
+    let mut records = Vec::with_capacity(args.rounds as usize);
+    for round in 0..args.rounds {
+        if args.rounds > 1 {
+            println!("\n=== Round {}/{} ===", round + 1, args.rounds);
+        }
+        match run_live_race(conf, round).await {
+            Ok(outcome) => {
+                let record = RaceRecord::from_outcome(&outcome, config_fingerprint.clone());
+                print_race_report(&record, args.timings);
+                match append_race(&history_path, &record) {
+                    Ok(()) => println!(
+                        "Race {} saved to history file {}",
+                        record.id,
+                        history_path.display()
+                    ),
+                    Err(e) => eprintln!("Warning: {}", e),
+                }
+                println!("--- LIVE RUN COMPLETE ---");
+                records.push(record);
+            }
+            Err(e) => {
+                eprintln!("\n--- Test Error: Race Failed ---");
+                eprintln!("{}", e);
+                if args.rounds == 1 {
+                    return ExitCode::FAILURE;
+                }
+                eprintln!("Skipping round {} and continuing.", round + 1);
+            }
+        }
+    }
+
+    if args.rounds > 1 {
+        if records.is_empty() {
+            eprintln!("\nAll {} rounds failed.", args.rounds);
+            return ExitCode::FAILURE;
+        }
+        let unfair = records.iter().filter(|r| r.unfair).count();
+        records.retain(|r| !r.unfair);
+        println!(
+            "\n### Aggregate Results ({} of {} rounds completed{})",
+            records.len() + unfair,
+            args.rounds,
+            if unfair > 0 {
+                format!(", {} unfair excluded", unfair)
+            } else {
+                String::new()
+            }
+        );
+        println!(
+            "{}",
+            generate_aggregate_table(&aggregate_endpoint_stats(&records))
+        );
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Constructs the race transactions and simulates each one on its endpoint.
+pub async fn run_simulate(conf: &Config) -> ExitCode {
+    let prepared_txs = match prepare_race(conf, 0).await {
+        Ok(prepared) => prepared.transactions,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if prepared_txs.is_empty() {
+        println!("No transactions were constructed. Exiting.");
+        return ExitCode::SUCCESS;
+    }
+
+    println!("\n--- DRY-RUN: Simulating Transactions ---");
+    let simulation_attempts = simulate_transactions_concurrently(prepared_txs).await;
+    println!("\nDry-run simulation attempts summary:");
+    let mut successful_simulations = 0;
+    for (i, attempt) in simulation_attempts.iter().enumerate() {
+        print!(
+            "  Sim {}: Tx (sig {}) for RPC {}. Amount: {} lamports. Duration: {}ms -> ",
+            i,
+            attempt.original_signature,
+            attempt.endpoint.name,
+            attempt.amount_lamports,
+            attempt.simulation_duration_ms
+        );
+        match &attempt.simulation_result {
+            Ok(sim_res) => {
+                if let Some(err) = &sim_res.err {
+                    println!("SIMULATION FAILED. Error: {:?}", err);
+                } else {
+                    successful_simulations += 1;
+                    println!("SIMULATION SUCCEEDED.");
+                }
+                if let Some(logs) = &sim_res.logs {
+                    if !logs.is_empty() {
+                        println!("    Logs:");
+                        for log in logs {
+                            println!("      {}", log);
+                        }
+                    }
+                }
+                if let Some(units) = sim_res.units_consumed {
+                    println!("    Units Consumed: {}", units);
+                }
+            }
+            Err(e) => {
+                println!("RPC ERROR during simulation: {}", e);
+            }
+        }
+    }
+    println!(
+        "Dry-run finished: {} successful simulations, {} failed or had RPC errors.",
+        successful_simulations,
+        simulation_attempts.len() - successful_simulations
+    );
+    println!("--- DRY-RUN COMPLETE ---");
+    ExitCode::SUCCESS
+}
+
+/// Prints the balances of both keypairs as seen by every enabled endpoint.
+pub async fn run_balance(conf: &Config) -> ExitCode {
+    let (account1, account2) = match load_accounts(conf) {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            eprintln!("Error loading accounts: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut rows = Vec::new();
+    for endpoint in conf.enabled_endpoints() {
+        println!("Fetching balances from RPC {}...", endpoint.name);
+        let rpc_client = endpoint.nonblocking_rpc_client(CommitmentConfig::confirmed());
+        let balance_1 = rpc_client
+            .get_balance_with_commitment(&account1.pubkey, CommitmentConfig::confirmed())
+            .await
+            .map(|response| response.value)
+            .map_err(redact_client_error);
+        let balance_2 = rpc_client
+            .get_balance_with_commitment(&account2.pubkey, CommitmentConfig::confirmed())
+            .await
+            .map(|response| response.value)
+            .map_err(redact_client_error);
+        rows.push(BalanceRow {
+            endpoint_name: endpoint.name,
+            balance_1,
+            balance_2,
+        });
+    }
+
+    println!("\n### Balances (confirmed commitment)");
+    println!(
+        "{}",
+        generate_balance_table(&account1.pubkey, &account2.pubkey, &rows)
+    );
+    ExitCode::SUCCESS
+}
+
+/// Moves the whole balance of one keypair, minus the fee, into the other.
+pub async fn run_sweep(conf: &Config, args: &SweepArgs) -> ExitCode {
+    let (richer, poorer) = match determine_account_roles(conf).await {
+        Ok(roles) => roles,
+        Err(e) => {
+            eprintln!("Error determining account roles: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (source, destination) = match args.into {
+        None => (poorer, richer),
+        Some(index) => {
+            let path = if index == 1 {
+                conf.keypair_path_1_expanded()
+            } else {
+                conf.keypair_path_2_expanded()
+            };
+            let destination_pubkey = match path.map_err(|e| e.to_string()).and_then(|p| {
+                read_keypair_file(&p)
+                    .map(|k| k.pubkey())
+                    .map_err(|e| format!("Failed to read keypair file '{}': {}", p.display(), e))
+            }) {
+                Ok(pubkey) => pubkey,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    return ExitCode::FAILURE;
+                }
+            };
+            if destination_pubkey == richer.pubkey {
+                (poorer, richer)
+            } else {
+                (richer, poorer)
+            }
+        }
+    };
+
+    let endpoints = conf.enabled_endpoints();
+    let rpc_client = endpoints[0].nonblocking_rpc_client(CommitmentConfig::confirmed());
+    let (transaction, amount_lamports) =
+        match construct_sweep_transaction(&source, &destination, &rpc_client).await {
+            Ok(Some(sweep)) => sweep,
+            Ok(None) => {
+                println!(
+                    "Nothing to sweep: {} holds {} lamports, which does not cover the fee.",
+                    source.pubkey, source.balance
+                );
+                return ExitCode::SUCCESS;
+            }
+            Err(e) => {
+                eprintln!("Error constructing sweep transaction: {}", e);
+                return ExitCode::FAILURE;
+            }
+        };
+
+    println!(
+        "Sweeping {} lamports from {} into {} via RPC {}...",
+        amount_lamports, source.pubkey, destination.pubkey, endpoints[0].name
+    );
+    match rpc_client.send_and_confirm_transaction(&transaction).await {
+        Ok(signature) => {
+            println!("Sweep confirmed. Signature: {}", signature);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!(
+                "Error sending sweep transaction: {}",
+                redact_client_error(e)
+            );
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Re-renders the report of a stored race, by default the most recent one.
+pub fn run_report(conf: &Config, args: &ReportArgs) -> ExitCode {
+    let history_path = match conf.history_path_expanded() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Error: Invalid history path: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let races = match load_races(&history_path) {
+        Ok(races) => races,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let race = match args.race_id {
+        Some(id) => races.iter().find(|r| r.id == id),
+        None => races.last(),
+    };
+    let Some(race) = race else {
+        eprintln!(
+            "Error: No stored race {}in {}.",
+            args.race_id.map_or(String::new(), |id| format!("{} ", id)),
+            history_path.display()
+        );
+        return ExitCode::FAILURE;
+    };
+
+    println!(
+        "Race {} started at {} UTC (config {})",
+        race.id,
+        race.started_at().format("%Y-%m-%d %H:%M:%S"),
+        race.config_fingerprint
+    );
+    print_race_report(race, args.timings);
+    ExitCode::SUCCESS
+}
+
+/// Prints stored races matching the filter, followed by per-RPC percentiles over them.
+pub fn run_history(conf: &Config, args: &HistoryArgs) -> ExitCode {
+    let history_path = match conf.history_path_expanded() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Error: Invalid history path: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let races = match load_races(&history_path) {
+        Ok(races) => races,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let filter = HistoryFilter {
+        endpoints: args.endpoints.clone(),
+        since: args.since,
+        until: args.until,
+        include_unfair: args.include_unfair,
+    };
+    let matching: Vec<RaceRecord> = races.into_iter().filter(|r| filter.matches(r)).collect();
+    if matching.is_empty() {
+        println!(
+            "No stored races match the given filters in {}.",
+            history_path.display()
+        );
+        return ExitCode::SUCCESS;
+    }
+
+    let listed = &matching[matching.len().saturating_sub(args.limit)..];
+    println!(
+        "\n### Race History ({} most recent of {} matching races)",
+        listed.len(),
+        matching.len()
+    );
+    println!("{}", generate_history_table(listed));
+
+    let mut stats = aggregate_endpoint_stats(&matching);
+    if !args.endpoints.is_empty() {
+        stats.retain(|s| args.endpoints.contains(&s.endpoint_name));
+    }
+    println!(
+        "### Per-RPC Statistics over {} races ({} to {} UTC)",
+        matching.len(),
+        matching[0].started_at().format("%Y-%m-%d %H:%M"),
+        matching[matching.len() - 1]
+            .started_at()
+            .format("%Y-%m-%d %H:%M")
+    );
+    println!("{}", generate_percentile_table(&stats));
+
+    let positions = aggregate_position_stats(&matching);
+    if !positions.is_empty() {
+        println!("### Wins by Dispatch Position");
+        println!("{}", generate_position_table(&positions));
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Creates, inspects, advances or closes the durable nonce account.
+pub async fn run_nonce(conf: &Config, command: &NonceCommand) -> ExitCode {
+    let (account1, account2) = match load_accounts(conf) {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            eprintln!("Error loading keypairs: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let endpoints = conf.enabled_endpoints();
+    println!("Using RPC: {}", endpoints[0].name);
+    let rpc_client = endpoints[0].nonblocking_rpc_client(CommitmentConfig::confirmed());
+
+    if let NonceCommand::Create(args) = command {
+        let authority = if args.authority == 1 {
+            &account1
+        } else {
+            &account2
+        };
+        let nonce_keypair = Keypair::new();
+        let result: Result<_, Box<dyn Error>> = async {
+            let transaction =
+                construct_create_nonce_transaction(authority, &nonce_keypair, &rpc_client).await?;
+            Ok(rpc_client
+                .send_and_confirm_transaction(&transaction)
+                .await
+                .map_err(redact_client_error)?)
+        }
+        .await;
+        return match result {
+            Ok(signature) => {
+                println!(
+                    "Created nonce account {} with authority {}. Signature: {}",
+                    nonce_keypair.pubkey(),
+                    authority.pubkey,
+                    signature
+                );
+                println!("Use it by adding to the configuration:");
+                println!(
+                    "  \"conflict_strategy\": {{ \"type\": \"durable_nonce\", \"nonce_account\": \"{}\" }}",
+                    nonce_keypair.pubkey()
+                );
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Error creating nonce account: {}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let nonce_pubkey = match &conf.conflict_strategy {
+        ConflictStrategyConfig::DurableNonce(nonce_config) => nonce_config.nonce_account,
+        _ => {
+            eprintln!(
+                "Error: conflict_strategy is not durable_nonce; no nonce account is configured."
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    let nonce = match fetch_nonce(&rpc_client, &nonce_pubkey).await {
+        Ok(nonce) => nonce,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("Nonce account: {}", nonce.pubkey);
+    println!("Authority: {}", nonce.authority);
+    println!("Stored nonce: {}", nonce.blockhash);
+    println!("Balance: {} lamports", nonce.lamports);
+    if let NonceCommand::Show = command {
+        return ExitCode::SUCCESS;
+    }
+
+    let result: Result<_, Box<dyn Error>> = async {
+        let authority = nonce_authority(&nonce, [&account1, &account2])?;
+        let transaction = if let NonceCommand::Close = command {
+            construct_close_nonce_transaction(&nonce, authority, &rpc_client).await?
+        } else {
+            construct_advance_nonce_transaction(&nonce, authority, &rpc_client).await?
+        };
+        Ok(rpc_client
+            .send_and_confirm_transaction(&transaction)
+            .await
+            .map_err(redact_client_error)?)
+    }
+    .await;
+    match result {
+        Ok(signature) => {
+            match command {
+                NonceCommand::Close => println!(
+                    "Nonce account closed; {} lamports returned to {}. Signature: {}",
+                    nonce.lamports, nonce.authority, signature
+                ),
+                _ => println!("Nonce advanced. Signature: {}", signature),
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,12 +1,209 @@
-use serde::Deserialize;
-use std::{error::Error, fs::File, io::BufReader, path::PathBuf};
+use crate::dispatch_order::DispatchOrderConfig;
+use crate::endpoint::Endpoint;
+use crate::fees::{AutoPriorityFeeConfig, ComputeBudgetConfig};
+use crate::monitoring::MonitoringConfig;
+use crate::rebroadcast::RebroadcastConfig;
+use crate::send_path::SendOptionsConfig;
+use crate::strategy::ConflictStrategyConfig;
+use crate::transactions::{TransactionFormatConfig, TransactionVersion};
+use serde::{Deserialize, Deserializer};
+use solana_sdk::{hash::hash, pubkey::Pubkey};
+use std::{collections::HashSet, fs::File, io::BufReader, path::PathBuf, str::FromStr};
+
+/// The on-disk shape of `config.json`, before legacy fields are normalized.
+#[derive(Deserialize)]
+struct RawConfig {
+    #[serde(default)]
+    endpoints: Vec<Endpoint>,
+    /// Legacy list of bare URLs; each becomes an endpoint named `rpcN`.
+    #[serde(default)]
+    rpc_urls: Vec<String>,
+    keypair_path_1: String,
+    keypair_path_2: String,
+    #[serde(default = "default_history_path")]
+    history_path: String,
+    #[serde(default)]
+    compute_budget: ComputeBudgetConfig,
+    #[serde(default)]
+    auto_priority_fee: Option<AutoPriorityFeeConfig>,
+    /// `sendTransaction` options for every RPC path, unless its endpoint overrides them.
+    #[serde(default)]
+    send_options: SendOptionsConfig,
+    #[serde(default)]
+    conflict_strategy: ConflictStrategyConfig,
+    #[serde(default)]
+    transaction_format: TransactionFormatConfig,
+    #[serde(default)]
+    rebroadcast: Option<RebroadcastConfig>,
+    #[serde(default = "default_max_dispatch_skew_us")]
+    max_dispatch_skew_us: u64,
+    #[serde(default)]
+    dispatch_order: DispatchOrderConfig,
+    #[serde(default)]
+    monitoring: MonitoringConfig,
+    /// Endpoints that only check the statuses of race transactions.
+    #[serde(default)]
+    observers: Vec<Endpoint>,
+}
+
+fn default_max_dispatch_skew_us() -> u64 {
+    1000
+}
+
+fn default_history_path() -> String {
+    "race_history.jsonl".to_string()
+}
+
+/// Deserializes a base58 pubkey string.
+pub fn deserialize_pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    Pubkey::from_str(&value)
+        .map_err(|e| serde::de::Error::custom(format!("invalid pubkey '{}': {}", value, e)))
+}
+
+/// Deserializes an optional base58 pubkey string.
+pub fn deserialize_optional_pubkey<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Pubkey>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) => Pubkey::from_str(&value)
+            .map(Some)
+            .map_err(|e| serde::de::Error::custom(format!("invalid pubkey '{}': {}", value, e))),
+        None => Ok(None),
+    }
+}
 
 /// Represents the application configuration loaded from `config.json`.
 #[derive(Deserialize, Debug)]
+#[serde(try_from = "RawConfig")]
 pub struct Config {
-    pub rpc_urls: Vec<String>,
+    /// All configured endpoints, including disabled ones.
+    pub endpoints: Vec<Endpoint>,
     pub keypair_path_1: String,
     pub keypair_path_2: String,
+    /// JSONL file every live race is appended to.
+    pub history_path: String,
+    /// Compute budget for every race transaction, unless its endpoint overrides it.
+    pub compute_budget: ComputeBudgetConfig,
+    /// When set, the global compute unit price is chosen from recent prioritization fees.
+    pub auto_priority_fee: Option<AutoPriorityFeeConfig>,
+    /// How the race transactions are made mutually exclusive.
+    pub conflict_strategy: ConflictStrategyConfig,
+    /// Legacy or v0 messages, optionally with an address lookup table.
+    pub transaction_format: TransactionFormatConfig,
+    /// When set, every accepted transaction is re-sent until the race is decided.
+    pub rebroadcast: Option<RebroadcastConfig>,
+    /// Races whose first and last dispatch are further apart are flagged as
+    /// unfair and left out of aggregate statistics.
+    pub max_dispatch_skew_us: u64,
+    /// Order in which endpoints get their transactions and are dispatched.
+    pub dispatch_order: DispatchOrderConfig,
+    /// How confirmations are detected.
+    pub monitoring: MonitoringConfig,
+    /// Neutral endpoints that decide the race by checking every transaction's
+    /// status; none of them sends. Includes disabled ones.
+    pub observers: Vec<Endpoint>,
+}
+
+impl TryFrom<RawConfig> for Config {
+    type Error = String;
+
+    fn try_from(raw: RawConfig) -> Result<Self, Self::Error> {
+        let mut endpoints = raw.endpoints;
+        for (i, url) in raw.rpc_urls.into_iter().enumerate() {
+            endpoints.push(Endpoint::from_url(format!("rpc{}", i + 1), url));
+        }
+
+        raw.send_options.validate()?;
+        let mut names = HashSet::new();
+        for endpoint in &mut endpoints {
+            if endpoint.name.trim().is_empty() {
+                return Err("Endpoint names must not be empty.".to_string());
+            }
+            if !names.insert(endpoint.name.as_str()) {
+                return Err(format!("Duplicate endpoint name '{}'.", endpoint.name));
+            }
+            if endpoint.url.trim().is_empty() {
+                return Err(format!("Endpoint '{}' has an empty URL.", endpoint.name));
+            }
+            endpoint.header_map()?;
+            endpoint.send_path.validate(&endpoint.name)?;
+            endpoint
+                .send_options
+                .validate()
+                .map_err(|e| format!("Endpoint '{}': {}", endpoint.name, e))?;
+            if !endpoint.send_path.uses_send_options() {
+                if endpoint.send_options != SendOptionsConfig::default() {
+                    return Err(format!(
+                        "Endpoint '{}': send_options only apply to the rpc and timed_rpc send paths.",
+                        endpoint.name
+                    ));
+                }
+            } else {
+                endpoint.send_options = endpoint.send_options.or(raw.send_options);
+            }
+        }
+
+        for observer in &raw.observers {
+            if observer.name.trim().is_empty() {
+                return Err("Observer names must not be empty.".to_string());
+            }
+            if !names.insert(observer.name.as_str()) {
+                return Err(format!(
+                    "Observer name '{}' is already used by another endpoint or observer.",
+                    observer.name
+                ));
+            }
+            if observer.url.trim().is_empty() {
+                return Err(format!("Observer '{}' has an empty URL.", observer.name));
+            }
+            observer.header_map()?;
+        }
+
+        raw.conflict_strategy.validate()?;
+        if raw.transaction_format.address_lookup_table.is_some()
+            && raw.transaction_format.version != TransactionVersion::V0
+        {
+            return Err(
+                "transaction_format.address_lookup_table requires \"version\": \"v0\".".to_string(),
+            );
+        }
+
+        if raw.rebroadcast.is_some_and(|r| r.interval_ms == 0) {
+            return Err("rebroadcast.interval_ms must be positive.".to_string());
+        }
+
+        if let Some(auto) = &raw.auto_priority_fee {
+            if raw.compute_budget.unit_price_micro_lamports.is_some() {
+                return Err(
+                    "Set either compute_budget.unit_price_micro_lamports or auto_priority_fee, not both."
+                        .to_string(),
+                );
+            }
+            if !(0.0..=100.0).contains(&auto.percentile) {
+                return Err(format!(
+                    "auto_priority_fee.percentile must be between 0 and 100, got {}.",
+                    auto.percentile
+                ));
+            }
+        }
+
+        Ok(Config {
+            endpoints,
+            keypair_path_1: raw.keypair_path_1,
+            keypair_path_2: raw.keypair_path_2,
+            history_path: raw.history_path,
+            compute_budget: raw.compute_budget,
+            auto_priority_fee: raw.auto_priority_fee,
+            conflict_strategy: raw.conflict_strategy,
+            transaction_format: raw.transaction_format,
+            rebroadcast: raw.rebroadcast,
+            max_dispatch_skew_us: raw.max_dispatch_skew_us,
+            dispatch_order: raw.dispatch_order,
+            monitoring: raw.monitoring,
+            observers: raw.observers,
+        })
+    }
 }
 
 impl Config {
@@ -14,13 +211,112 @@ impl Config {
     ///
     /// The path is expected to point to a JSON file structured according
     /// to the `Config` definition.
-    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
-        let file = File::open(path)?;
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path)
+            .map_err(|e| format!("Configuration file not found at '{}': {}", path, e))?;
         let reader = BufReader::new(file);
-        let config: Config = serde_json::from_reader(reader)?;
+        let config: Config = serde_json::from_reader(reader)
+            .map_err(|e| format!("Failed to parse configuration file '{}': {}", path, e))?;
         Ok(config)
     }
 
+    /// Returns the endpoints that take part in races, in configuration order.
+    pub fn enabled_endpoints(&self) -> Vec<Endpoint> {
+        self.endpoints
+            .iter()
+            .filter(|e| e.enabled)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the observers that take part in monitoring.
+    pub fn enabled_observers(&self) -> Vec<Endpoint> {
+        self.observers
+            .iter()
+            .filter(|o| o.enabled)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns a stable digest of the race-relevant settings, so that stored
+    /// races run under different configurations can be told apart.
+    ///
+    /// Only endpoint names, redacted URLs, timeouts and fee settings are
+    /// included; secrets never are.
+    pub fn fingerprint(&self) -> String {
+        let mut canonical = String::new();
+        for endpoint in self.enabled_endpoints() {
+            canonical.push_str(&format!(
+                "endpoint={}|{}|{}|{:?}|{};",
+                endpoint.name,
+                endpoint.redacted_url(),
+                endpoint.timeout().as_millis(),
+                self.compute_budget_for(&endpoint),
+                endpoint.send_path.kind()
+            ));
+            if endpoint.send_options != SendOptionsConfig::default() {
+                canonical.push_str(&format!("send_options={:?};", endpoint.send_options));
+            }
+        }
+        canonical.push_str(&format!("conflict_strategy={:?};", self.conflict_strategy));
+        canonical.push_str(&format!(
+            "transaction_format={:?};",
+            self.transaction_format
+        ));
+        if let Some(auto) = &self.auto_priority_fee {
+            canonical.push_str(&format!("auto_priority_fee={:?};", auto));
+        }
+        if self.max_dispatch_skew_us != default_max_dispatch_skew_us() {
+            canonical.push_str(&format!(
+                "max_dispatch_skew_us={};",
+                self.max_dispatch_skew_us
+            ));
+        }
+        if self.dispatch_order != DispatchOrderConfig::Fixed {
+            canonical.push_str(&format!("dispatch_order={:?};", self.dispatch_order));
+        }
+        if let Some(rebroadcast) = &self.rebroadcast {
+            canonical.push_str(&format!("rebroadcast={:?};", rebroadcast));
+        }
+        for observer in self.enabled_observers() {
+            canonical.push_str(&format!(
+                "observer={}|{};",
+                observer.name,
+                observer.redacted_url()
+            ));
+        }
+        // Destructured so that a new monitoring field has to be added here.
+        let MonitoringConfig {
+            method,
+            target_commitment,
+            await_finalized,
+        } = self.monitoring;
+        let default = MonitoringConfig::default();
+        if method != default.method {
+            canonical.push_str(&format!("monitoring.method={:?};", method));
+        }
+        if target_commitment != default.target_commitment {
+            canonical.push_str(&format!(
+                "monitoring.target_commitment={:?};",
+                target_commitment
+            ));
+        }
+        if await_finalized != default.await_finalized {
+            canonical.push_str(&format!("monitoring.await_finalized={};", await_finalized));
+        }
+        hash(canonical.as_bytes()).to_string()
+    }
+
+    /// Returns the compute budget for transactions sent through `endpoint`.
+    pub fn compute_budget_for(&self, endpoint: &Endpoint) -> ComputeBudgetConfig {
+        endpoint.compute_budget.or(self.compute_budget)
+    }
+
+    /// Returns the expanded `PathBuf` for `history_path`.
+    pub fn history_path_expanded(&self) -> Result<PathBuf, String> {
+        Self::expand_path(&self.history_path)
+    }
+
     /// Expands a path string, resolving tilde (~) to the user's home directory.
     fn expand_path(path_str: &str) -> Result<PathBuf, String> {
         let expanded_path_cow = shellexpand::tilde(path_str);
@@ -62,14 +358,271 @@ mod tests {
 
         let loaded_config = Config::load(tmp_file.path().to_str().unwrap()).unwrap();
 
-        assert_eq!(
-            loaded_config.rpc_urls,
-            vec!["http://localhost:8899".to_string()]
-        );
+        assert_eq!(loaded_config.endpoints.len(), 1);
+        assert_eq!(loaded_config.endpoints[0].name, "rpc1");
+        assert_eq!(loaded_config.endpoints[0].url, "http://localhost:8899");
         assert_eq!(loaded_config.keypair_path_1, "/tmp/kp1.json");
         assert_eq!(loaded_config.keypair_path_2, "/tmp/kp2.json");
     }
 
+    #[test]
+    fn test_config_load_named_endpoints() {
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        let config_content = r#"
+        {
+            "endpoints": [
+                {
+                    "name": "provider-a",
+                    "url": "https://rpc.example.com/?api-key=secret",
+                    "headers": { "Authorization": "Bearer token" },
+                    "timeout_ms": 5000
+                },
+                { "name": "local", "url": "http://localhost:8899", "enabled": false }
+            ],
+            "keypair_path_1": "/tmp/kp1.json",
+            "keypair_path_2": "/tmp/kp2.json"
+        }
+        "#;
+        write!(tmp_file, "{}", config_content).unwrap();
+
+        let loaded_config = Config::load(tmp_file.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(loaded_config.endpoints.len(), 2);
+        let provider = &loaded_config.endpoints[0];
+        assert_eq!(provider.name, "provider-a");
+        assert_eq!(provider.headers["Authorization"], "Bearer token");
+        assert_eq!(provider.timeout_ms, Some(5000));
+        assert!(provider.enabled);
+        let enabled = loaded_config.enabled_endpoints();
+        assert_eq!(enabled.len(), 1);
+        assert_eq!(enabled[0].name, "provider-a");
+        assert!(!format!("{:?}", loaded_config).contains("secret"));
+    }
+
+    #[test]
+    fn test_config_load_duplicate_endpoint_names() {
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        let config_content = r#"
+        {
+            "endpoints": [
+                { "name": "rpc1", "url": "http://localhost:8899" }
+            ],
+            "rpc_urls": ["http://localhost:8899"],
+            "keypair_path_1": "/tmp/kp1.json",
+            "keypair_path_2": "/tmp/kp2.json"
+        }
+        "#;
+        write!(tmp_file, "{}", config_content).unwrap();
+
+        let result = Config::load(tmp_file.path().to_str().unwrap());
+        assert!(result
+            .unwrap_err()
+            .contains("Duplicate endpoint name 'rpc1'"));
+    }
+
+    #[test]
+    fn test_config_load_auto_priority_fee() {
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        let config_content = r#"
+        {
+            "rpc_urls": ["http://localhost:8899"],
+            "keypair_path_1": "/tmp/kp1.json",
+            "keypair_path_2": "/tmp/kp2.json",
+            "auto_priority_fee": { "max_micro_lamports": 50000 }
+        }
+        "#;
+        write!(tmp_file, "{}", config_content).unwrap();
+
+        let loaded_config = Config::load(tmp_file.path().to_str().unwrap()).unwrap();
+        let auto = loaded_config.auto_priority_fee.unwrap();
+        assert_eq!(auto.percentile, 75.0);
+        assert_eq!(auto.lookback_slots, 150);
+        assert_eq!(auto.max_micro_lamports, 50_000);
+
+        let mut conflicting = NamedTempFile::new().unwrap();
+        write!(
+            conflicting,
+            "{}",
+            config_content.replace(
+                r#""rpc_urls""#,
+                r#""compute_budget": { "unit_price_micro_lamports": 1 }, "rpc_urls""#
+            )
+        )
+        .unwrap();
+        let result = Config::load(conflicting.path().to_str().unwrap());
+        assert!(result.unwrap_err().contains("not both"));
+    }
+
+    #[test]
+    fn test_config_load_durable_nonce_strategy() {
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        let config_content = r#"
+        {
+            "rpc_urls": ["http://localhost:8899"],
+            "keypair_path_1": "/tmp/kp1.json",
+            "keypair_path_2": "/tmp/kp2.json",
+            "conflict_strategy": {
+                "type": "durable_nonce",
+                "nonce_account": "11111111111111111111111111111111"
+            }
+        }
+        "#;
+        write!(tmp_file, "{}", config_content).unwrap();
+
+        let loaded_config = Config::load(tmp_file.path().to_str().unwrap()).unwrap();
+        match loaded_config.conflict_strategy {
+            ConflictStrategyConfig::DurableNonce(nonce) => {
+                assert_eq!(nonce.nonce_account, solana_sdk::system_program::id());
+                assert_eq!(nonce.amount_lamports, 1_000);
+            }
+            other => panic!("unexpected strategy {:?}", other),
+        }
+
+        let mut invalid = NamedTempFile::new().unwrap();
+        write!(
+            invalid,
+            "{}",
+            config_content.replace("11111111111111111111111111111111", "not-a-pubkey")
+        )
+        .unwrap();
+        let result = Config::load(invalid.path().to_str().unwrap());
+        assert!(result.unwrap_err().contains("invalid pubkey"));
+    }
+
+    #[test]
+    fn test_config_load_lookup_table_requires_v0() {
+        let config_content = r#"
+        {
+            "rpc_urls": ["http://localhost:8899"],
+            "keypair_path_1": "/tmp/kp1.json",
+            "keypair_path_2": "/tmp/kp2.json",
+            "transaction_format": {
+                "version": "VERSION",
+                "address_lookup_table": "11111111111111111111111111111111"
+            }
+        }
+        "#;
+        let load = |version: &str| {
+            let mut tmp_file = NamedTempFile::new().unwrap();
+            write!(tmp_file, "{}", config_content.replace("VERSION", version)).unwrap();
+            Config::load(tmp_file.path().to_str().unwrap())
+        };
+
+        let loaded_config = load("v0").unwrap();
+        assert_eq!(
+            loaded_config.transaction_format.version,
+            TransactionVersion::V0
+        );
+        assert!(load("legacy").unwrap_err().contains("requires"));
+    }
+
+    #[test]
+    fn test_config_load_send_options_merge_into_rpc_endpoints() {
+        let config_content = r#"
+        {
+            "endpoints": [
+                { "name": "a", "url": "http://a", "send_options": { "max_retries": 3 } },
+                { "name": "b", "url": "http://b", "send_options": { "skip_preflight": false } }
+            ],
+            "keypair_path_1": "/tmp/kp1.json",
+            "keypair_path_2": "/tmp/kp2.json",
+            "send_options": { "skip_preflight": true, "preflight_commitment": "confirmed" }
+        }
+        "#;
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        write!(tmp_file, "{}", config_content).unwrap();
+
+        let loaded_config = Config::load(tmp_file.path().to_str().unwrap()).unwrap();
+
+        let a = loaded_config.endpoints[0].send_options;
+        assert_eq!(a.skip_preflight, Some(true));
+        assert_eq!(a.max_retries, Some(3));
+        assert_eq!(
+            a.preflight_commitment,
+            Some(solana_sdk::commitment_config::CommitmentLevel::Confirmed)
+        );
+        assert_eq!(
+            loaded_config.endpoints[1].send_options.skip_preflight,
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_config_load_send_options_rejected_for_tpu() {
+        let config_content = r#"
+        {
+            "endpoints": [
+                { "name": "a", "url": "http://a", "send_path": { "type": "tpu" }, "send_options": { "skip_preflight": true } }
+            ],
+            "keypair_path_1": "/tmp/kp1.json",
+            "keypair_path_2": "/tmp/kp2.json"
+        }
+        "#;
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        write!(tmp_file, "{}", config_content).unwrap();
+
+        let err = Config::load(tmp_file.path().to_str().unwrap()).unwrap_err();
+        assert!(err.contains("only apply to the rpc and timed_rpc send paths"));
+    }
+
+    #[test]
+    fn test_config_load_observer_name_must_be_unique() {
+        let config_content = r#"
+        {
+            "endpoints": [{ "name": "a", "url": "http://a" }],
+            "observers": [{ "name": "a", "url": "http://observer" }],
+            "keypair_path_1": "/tmp/kp1.json",
+            "keypair_path_2": "/tmp/kp2.json"
+        }
+        "#;
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        write!(tmp_file, "{}", config_content).unwrap();
+
+        let err = Config::load(tmp_file.path().to_str().unwrap()).unwrap_err();
+        assert!(err.contains("Observer name 'a' is already used"));
+    }
+
+    /// Loads a minimal configuration with `extra` fields appended.
+    fn load_with(extra: &str) -> Config {
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        write!(
+            tmp_file,
+            r#"{{ "rpc_urls": ["http://localhost:8899"], "keypair_path_1": "/tmp/kp1.json", "keypair_path_2": "/tmp/kp2.json"{} }}"#,
+            extra
+        )
+        .unwrap();
+        Config::load(tmp_file.path().to_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_fingerprint_includes_max_dispatch_skew() {
+        let default = load_with("");
+        assert_eq!(
+            default.fingerprint(),
+            load_with(r#", "max_dispatch_skew_us": 1000"#).fingerprint()
+        );
+        assert_ne!(
+            default.fingerprint(),
+            load_with(r#", "max_dispatch_skew_us": 5000"#).fingerprint()
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_includes_monitoring() {
+        let processed = load_with(r#", "monitoring": { "target_commitment": "processed" }"#);
+        let awaiting = load_with(
+            r#", "monitoring": { "target_commitment": "processed", "await_finalized": true }"#,
+        );
+
+        assert!(awaiting.monitoring.await_finalized);
+        assert_ne!(processed.fingerprint(), awaiting.fingerprint());
+        assert_ne!(processed.fingerprint(), load_with("").fingerprint());
+        assert_eq!(
+            load_with("").fingerprint(),
+            load_with(r#", "monitoring": { "await_finalized": false }"#).fingerprint()
+        );
+    }
+
     #[test]
     fn test_config_load_file_not_found() {
         let result = Config::load("non_existent_config.json");
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How endpoints are ordered for a race. The order decides which transaction
+/// (and so which amount) each endpoint gets and in which order the sender
+/// threads are spawned.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum DispatchOrderConfig {
+    /// Configuration order in every race.
+    #[default]
+    Fixed,
+    /// A fresh random permutation per race. With `seed`, each round's
+    /// permutation is derived from the seed and the round number, so rounds
+    /// differ but a run with the same seed replays all of them.
+    Shuffle {
+        #[serde(default)]
+        seed: Option<u64>,
+    },
+    /// Configuration order rotated by one position per round.
+    RoundRobin,
+}
+
+/// The order a race used, as stored with it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DispatchOrder {
+    pub mode: String,
+    /// Seed of the race's shuffle, after deriving it for the round; passing it
+    /// as `seed` replays the permutation in the first round.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Configuration indices of the endpoints, in dispatch order.
+    pub permutation: Vec<usize>,
+}
+
+impl DispatchOrderConfig {
+    /// Computes the order of `count` endpoints for round `round` (0-based).
+    pub fn order(&self, count: usize, round: u32) -> DispatchOrder {
+        match *self {
+            DispatchOrderConfig::Fixed => DispatchOrder {
+                mode: "fixed".to_string(),
+                seed: None,
+                permutation: (0..count).collect(),
+            },
+            DispatchOrderConfig::Shuffle { seed } => {
+                let seed = seed.map_or_else(random_seed, |seed| round_seed(seed, round));
+                DispatchOrder {
+                    mode: "shuffle".to_string(),
+                    seed: Some(seed),
+                    permutation: shuffled(count, seed),
+                }
+            }
+            DispatchOrderConfig::RoundRobin => {
+                let mut permutation: Vec<usize> = (0..count).collect();
+                if count > 0 {
+                    permutation.rotate_left(round as usize % count);
+                }
+                DispatchOrder {
+                    mode: "round_robin".to_string(),
+                    seed: None,
+                    permutation,
+                }
+            }
+        }
+    }
+}
+
+fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+/// SplitMix64; simple and fully specified, so stored seeds replay the same
+/// permutation regardless of library versions.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Seed of round `round` (0-based) of a run configured with `seed`. Round 0
+/// uses `seed` itself, so a stored race seed replays as the first round.
+fn round_seed(seed: u64, round: u32) -> u64 {
+    if round == 0 {
+        return seed;
+    }
+    let mut state = round as u64;
+    seed ^ splitmix64(&mut state)
+}
+
+/// Fisher-Yates shuffle of `0..count` driven by `seed`.
+fn shuffled(count: usize, seed: u64) -> Vec<usize> {
+    let mut permutation: Vec<usize> = (0..count).collect();
+    let mut state = seed;
+    for i in (1..count).rev() {
+        let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+        permutation.swap(i, j);
+    }
+    permutation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shuffle_replays_from_seed() {
+        let config = DispatchOrderConfig::Shuffle { seed: Some(42) };
+        let first = config.order(8, 0);
+        let second = config.order(8, 1);
+
+        assert_eq!(first, config.order(8, 0));
+        assert_eq!(second, config.order(8, 1));
+        assert_eq!(first.seed, Some(42));
+        assert_ne!(first.seed, second.seed);
+        assert_ne!(first.permutation, second.permutation);
+        // A stored seed replays its race's permutation.
+        assert_eq!(
+            DispatchOrderConfig::Shuffle { seed: second.seed }
+                .order(8, 0)
+                .permutation,
+            second.permutation
+        );
+        let mut sorted = first.permutation.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..8).collect::<Vec<_>>());
+        assert_ne!(
+            shuffled(8, 42),
+            shuffled(8, 43),
+            "different seeds should give different orders"
+        );
+    }
+
+    #[test]
+    fn test_round_robin_rotates_per_round() {
+        let config = DispatchOrderConfig::RoundRobin;
+        assert_eq!(config.order(3, 0).permutation, vec![0, 1, 2]);
+        assert_eq!(config.order(3, 1).permutation, vec![1, 2, 0]);
+        assert_eq!(config.order(3, 4).permutation, vec![1, 2, 0]);
+    }
+}
@@ -0,0 +1,265 @@
+use crate::endpoint::redact_client_error;
+use crate::stats::percentile;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, instruction::Instruction, pubkey::Pubkey,
+};
+use std::error::Error;
+
+/// Base fee charged per transaction signature, in lamports.
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+/// Compute units the runtime allots per instruction when no limit is requested.
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+/// Upper bound on a transaction's compute unit limit.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// Compute budget applied to race transactions via ComputeBudget instructions.
+///
+/// Configured globally and optionally per endpoint; unset endpoint fields
+/// fall back to the global value.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComputeBudgetConfig {
+    #[serde(default)]
+    pub unit_limit: Option<u32>,
+    #[serde(default)]
+    pub unit_price_micro_lamports: Option<u64>,
+}
+
+impl ComputeBudgetConfig {
+    /// Returns `self` with unset fields taken from `fallback`.
+    pub fn or(self, fallback: ComputeBudgetConfig) -> Self {
+        ComputeBudgetConfig {
+            unit_limit: self.unit_limit.or(fallback.unit_limit),
+            unit_price_micro_lamports: self
+                .unit_price_micro_lamports
+                .or(fallback.unit_price_micro_lamports),
+        }
+    }
+
+    /// The ComputeBudget instructions to prepend to a transaction.
+    pub fn instructions(&self) -> Vec<Instruction> {
+        let mut instructions = Vec::new();
+        if let Some(units) = self.unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+        if let Some(micro_lamports) = self.unit_price_micro_lamports {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
+                micro_lamports,
+            ));
+        }
+        instructions
+    }
+
+    /// Compute unit limit the runtime will apply to a transaction with
+    /// `other_instruction_count` non-ComputeBudget instructions.
+    pub fn effective_unit_limit(&self, other_instruction_count: usize) -> u32 {
+        self.unit_limit
+            .unwrap_or(
+                DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT
+                    .saturating_mul(other_instruction_count as u32),
+            )
+            .min(MAX_COMPUTE_UNIT_LIMIT)
+    }
+
+    /// Prioritization fee in lamports: unit price times unit limit, rounded up.
+    pub fn priority_fee_lamports(&self, other_instruction_count: usize) -> u64 {
+        let micro_lamports = self.unit_price_micro_lamports.unwrap_or(0) as u128
+            * self.effective_unit_limit(other_instruction_count) as u128;
+        micro_lamports.div_ceil(MICRO_LAMPORTS_PER_LAMPORT) as u64
+    }
+
+    /// Total fee in lamports: signature fees plus the prioritization fee.
+    pub fn total_fee_lamports(&self, num_signatures: usize, other_instruction_count: usize) -> u64 {
+        LAMPORTS_PER_SIGNATURE * num_signatures as u64
+            + self.priority_fee_lamports(other_instruction_count)
+    }
+}
+
+fn default_auto_percentile() -> f64 {
+    75.0
+}
+
+fn default_auto_lookback_slots() -> u64 {
+    150
+}
+
+/// Derives the compute unit price from `getRecentPrioritizationFees` before each race.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct AutoPriorityFeeConfig {
+    /// Percentile of the recent per-slot fees to pay, e.g. 75 for p75.
+    #[serde(default = "default_auto_percentile")]
+    pub percentile: f64,
+    /// Only slots within this distance of the newest sample are considered.
+    #[serde(default = "default_auto_lookback_slots")]
+    pub lookback_slots: u64,
+    /// Upper bound on the selected price, in micro-lamports per compute unit.
+    pub max_micro_lamports: u64,
+}
+
+/// One slot's minimum prioritization fee as reported by the RPC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrioritizationFeeSample {
+    pub slot: u64,
+    pub micro_lamports: u64,
+}
+
+/// The outcome of automatic fee selection, stored with the race.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeSelection {
+    pub config: AutoPriorityFeeConfig,
+    /// Samples within the lookback window that the percentile was taken over.
+    pub samples: Vec<PrioritizationFeeSample>,
+    pub selected_micro_lamports: u64,
+    /// Whether `max_micro_lamports` lowered the percentile value.
+    pub capped: bool,
+}
+
+impl AutoPriorityFeeConfig {
+    /// Picks the configured percentile over the samples in the lookback window, capped.
+    pub fn select(&self, samples: &[PrioritizationFeeSample]) -> FeeSelection {
+        let newest_slot = samples.iter().map(|s| s.slot).max().unwrap_or(0);
+        let mut window: Vec<PrioritizationFeeSample> = samples
+            .iter()
+            .filter(|s| s.slot + self.lookback_slots > newest_slot)
+            .copied()
+            .collect();
+        window.sort_by_key(|s| s.slot);
+
+        let mut fees: Vec<u64> = window.iter().map(|s| s.micro_lamports).collect();
+        fees.sort_unstable();
+        let percentile_value = if fees.is_empty() {
+            0
+        } else {
+            percentile(&fees, self.percentile)
+        };
+
+        FeeSelection {
+            config: *self,
+            samples: window,
+            selected_micro_lamports: percentile_value.min(self.max_micro_lamports),
+            capped: percentile_value > self.max_micro_lamports,
+        }
+    }
+
+    /// Queries recent prioritization fees for the sender and the recipient (the
+    /// accounts every race transfer write-locks) and selects a price.
+    pub async fn fetch_and_select(
+        &self,
+        rpc_client: &RpcClient,
+        sender: &Pubkey,
+        recipient: &Pubkey,
+    ) -> Result<FeeSelection, Box<dyn Error>> {
+        let samples: Vec<PrioritizationFeeSample> = rpc_client
+            .get_recent_prioritization_fees(&[*sender, *recipient])
+            .await
+            .map_err(redact_client_error)?
+            .into_iter()
+            .map(|fee| PrioritizationFeeSample {
+                slot: fee.slot,
+                micro_lamports: fee.prioritization_fee,
+            })
+            .collect();
+        Ok(self.select(&samples))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_or_prefers_own_fields() {
+        let global = ComputeBudgetConfig {
+            unit_limit: Some(1_000),
+            unit_price_micro_lamports: Some(10),
+        };
+        let endpoint = ComputeBudgetConfig {
+            unit_limit: None,
+            unit_price_micro_lamports: Some(99),
+        };
+        assert_eq!(
+            endpoint.or(global),
+            ComputeBudgetConfig {
+                unit_limit: Some(1_000),
+                unit_price_micro_lamports: Some(99),
+            }
+        );
+    }
+
+    #[test]
+    fn test_fee_without_compute_budget() {
+        let budget = ComputeBudgetConfig::default();
+        assert!(budget.instructions().is_empty());
+        assert_eq!(budget.total_fee_lamports(1, 1), 5_000);
+    }
+
+    #[test]
+    fn test_priority_fee_rounds_up() {
+        let budget = ComputeBudgetConfig {
+            unit_limit: Some(450),
+            unit_price_micro_lamports: Some(1_000_001),
+        };
+        assert_eq!(budget.instructions().len(), 2);
+        // 450 * 1_000_001 micro-lamports = 450.00045 lamports.
+        assert_eq!(budget.priority_fee_lamports(1), 451);
+        assert_eq!(budget.total_fee_lamports(1, 1), 5_451);
+    }
+
+    #[test]
+    fn test_priority_fee_uses_default_limit() {
+        let budget = ComputeBudgetConfig {
+            unit_limit: None,
+            unit_price_micro_lamports: Some(1_000),
+        };
+        assert_eq!(budget.effective_unit_limit(1), 200_000);
+        assert_eq!(budget.priority_fee_lamports(1), 200);
+    }
+
+    fn samples(fees: &[(u64, u64)]) -> Vec<PrioritizationFeeSample> {
+        fees.iter()
+            .map(|&(slot, micro_lamports)| PrioritizationFeeSample {
+                slot,
+                micro_lamports,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_auto_select_percentile_within_lookback() {
+        let config = AutoPriorityFeeConfig {
+            percentile: 75.0,
+            lookback_slots: 4,
+            max_micro_lamports: 1_000,
+        };
+        // Slot 100 is outside the 4-slot window ending at 104.
+        let selection = config.select(&samples(&[
+            (100, 900),
+            (101, 10),
+            (102, 40),
+            (103, 20),
+            (104, 30),
+        ]));
+
+        assert_eq!(selection.samples.len(), 4);
+        assert_eq!(selection.selected_micro_lamports, 30);
+        assert!(!selection.capped);
+    }
+
+    #[test]
+    fn test_auto_select_caps_and_handles_no_samples() {
+        let config = AutoPriorityFeeConfig {
+            percentile: 50.0,
+            lookback_slots: 150,
+            max_micro_lamports: 25,
+        };
+        let selection = config.select(&samples(&[(1, 50), (2, 60)]));
+        assert_eq!(selection.selected_micro_lamports, 25);
+        assert!(selection.capped);
+
+        let empty = config.select(&[]);
+        assert_eq!(empty.selected_micro_lamports, 0);
+        assert!(empty.samples.is_empty());
+    }
+}
@@ -0,0 +1,658 @@
+use crate::dispatch_order::DispatchOrder;
+use crate::fees::FeeSelection;
+use crate::monitoring::{Commitment, CommitmentTimes, DetectionMethod};
+use crate::ordering::OnChainOrder;
+use crate::race::RaceOutcome;
+use crate::send_path::SendOptionsConfig;
+use crate::slot_clock::SlotTimeline;
+use crate::timing::SendTiming;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A persisted send attempt. `error` is `None` when the RPC accepted the transaction.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SendAttemptRecord {
+    pub endpoint_name: String,
+    pub signature: String,
+    pub amount_lamports: u64,
+    pub error: Option<String>,
+    pub send_started_at_ms: u64,
+    pub send_duration_ms: u64,
+    #[serde(default)]
+    pub compute_unit_limit: Option<u32>,
+    #[serde(default)]
+    pub compute_unit_price_micro_lamports: Option<u64>,
+    /// Signature plus prioritization fee; absent in races stored before fees were tracked.
+    #[serde(default)]
+    pub fee_lamports: Option<u64>,
+    /// Submission mechanism, e.g. `rpc` or `tpu`; absent in races stored before paths existed.
+    #[serde(default)]
+    pub send_path: Option<String>,
+    /// Bundle ID and final `getBundleStatuses` result, for bundle paths.
+    #[serde(default)]
+    pub bundle_id: Option<String>,
+    #[serde(default)]
+    pub bundle_status: Option<String>,
+    /// Effective `sendTransaction` options, for RPC paths.
+    #[serde(default)]
+    pub send_options: Option<SendOptionsConfig>,
+    /// Sends after the initial one; 0 when rebroadcasting was off.
+    #[serde(default)]
+    pub rebroadcasts: u32,
+    /// The send that landed, 0 being the initial one; only known when rebroadcasting.
+    #[serde(default)]
+    pub landed_send: Option<u32>,
+    /// Round trip of the connection warm-up before dispatch, if the path has one.
+    #[serde(default)]
+    pub warm_up_ms: Option<u64>,
+    #[serde(default)]
+    pub warm_up_error: Option<String>,
+    /// Phases of the send, for paths that time them.
+    #[serde(default)]
+    pub timing: Option<SendTiming>,
+    /// Setup of the connection the send went over, which the warm-up may have opened.
+    #[serde(default)]
+    pub connection_timing: Option<SendTiming>,
+    /// Position in the race's dispatch order, 0 being first.
+    #[serde(default)]
+    pub position: Option<u32>,
+    /// Cluster slot at dispatch.
+    #[serde(default)]
+    pub dispatch_slot: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WinnerRecord {
+    pub endpoint_name: String,
+    pub signature: String,
+    pub amount_lamports: u64,
+    /// `None` when the on-chain winner was not seen at any commitment before
+    /// monitoring ended.
+    pub time_to_confirm_ms: Option<u64>,
+    pub slot: u64,
+    pub confirmation_status: String,
+    /// `None` for races stored before the detection method was recorded, which were polled.
+    #[serde(default)]
+    pub detection_method: Option<DetectionMethod>,
+    /// Observer that decided the race; `None` when the sending endpoint was asked.
+    #[serde(default)]
+    pub observer: Option<String>,
+    /// The sending endpoint's own time to confirm, when an observer decided.
+    #[serde(default)]
+    pub sender_view_ms: Option<u64>,
+    /// When the winner was seen processed, confirmed and finalized.
+    #[serde(default)]
+    pub commitment_times: Option<CommitmentTimes>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NonWinnerRecord {
+    pub endpoint_name: String,
+    pub signature: String,
+    pub amount_lamports: u64,
+    pub status_summary: String,
+    pub last_known_slot: Option<u64>,
+    #[serde(default)]
+    pub commitment_times: Option<CommitmentTimes>,
+}
+
+/// One line of the history file: everything needed to re-analyze a finished race.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RaceRecord {
+    /// Unix milliseconds at which the race started; doubles as the race id.
+    pub id: u64,
+    pub config_fingerprint: String,
+    pub send_attempts: Vec<SendAttemptRecord>,
+    /// The transaction that succeeded on chain when its block could be read,
+    /// otherwise the first one observed reaching the target commitment.
+    pub winner: Option<WinnerRecord>,
+    /// The transaction observed first, when it is not the on-chain winner.
+    #[serde(default)]
+    pub first_observed: Option<WinnerRecord>,
+    pub non_winners: Vec<NonWinnerRecord>,
+    /// Present when the compute unit price was chosen automatically.
+    #[serde(default)]
+    pub fee_selection: Option<FeeSelection>,
+    /// Time between the first and the last dispatch, in microseconds.
+    #[serde(default)]
+    pub dispatch_skew_us: Option<u64>,
+    /// Set when the dispatch skew exceeded the configured maximum.
+    #[serde(default)]
+    pub unfair: bool,
+    /// Set when only some paths timed the request write, so the skew was
+    /// measured between submission starts.
+    #[serde(default)]
+    pub mixed_dispatch_reference: bool,
+    /// How endpoints were ordered; absent in races stored before orders were configurable.
+    #[serde(default)]
+    pub dispatch_order: Option<DispatchOrder>,
+    /// Race transactions found in the blocks they landed in.
+    #[serde(default)]
+    pub on_chain_order: Option<OnChainOrder>,
+    /// When each slot of the race window was first seen; absent in races
+    /// stored before the slot clock existed.
+    #[serde(default)]
+    pub slot_timeline: Option<SlotTimeline>,
+    /// Whether monitoring followed the winner until it finalized.
+    #[serde(default)]
+    pub finalized_tracked: bool,
+}
+
+/// Builds race records for tests.
+#[cfg(test)]
+pub struct RaceBuilder(RaceRecord);
+
+#[cfg(test)]
+impl RaceBuilder {
+    pub fn new(id: u64) -> Self {
+        RaceBuilder(RaceRecord {
+            id,
+            config_fingerprint: "fp".to_string(),
+            ..Default::default()
+        })
+    }
+
+    /// Adds an accepted attempt by `name`, signed `sig-{id}-{name}` and sent
+    /// at the race start.
+    pub fn attempt(self, name: &str) -> Self {
+        self.attempt_with(name, |_| {})
+    }
+
+    /// Adds an attempt like [`RaceBuilder::attempt`], adjusted by `edit`.
+    pub fn attempt_with(mut self, name: &str, edit: impl FnOnce(&mut SendAttemptRecord)) -> Self {
+        let mut attempt = SendAttemptRecord {
+            endpoint_name: name.to_string(),
+            signature: format!("sig-{}-{}", self.0.id, name),
+            amount_lamports: 1,
+            send_started_at_ms: self.0.id,
+            send_duration_ms: 10,
+            ..Default::default()
+        };
+        edit(&mut attempt);
+        self.0.send_attempts.push(attempt);
+        self
+    }
+
+    /// Makes `name`'s attempt the winner, confirmed in slot 1.
+    pub fn winner(mut self, name: &str, time_to_confirm_ms: u64) -> Self {
+        let attempt = self.attempt_named(name);
+        self.0.winner = Some(WinnerRecord {
+            endpoint_name: attempt.endpoint_name.clone(),
+            signature: attempt.signature.clone(),
+            amount_lamports: attempt.amount_lamports,
+            time_to_confirm_ms: Some(time_to_confirm_ms),
+            slot: 1,
+            confirmation_status: "Confirmed".to_string(),
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Adds `name`'s attempt to the non-winners, last seen in `last_known_slot`.
+    pub fn non_winner(mut self, name: &str, last_known_slot: Option<u64>) -> Self {
+        let attempt = self.attempt_named(name);
+        let non_winner = NonWinnerRecord {
+            endpoint_name: attempt.endpoint_name.clone(),
+            signature: attempt.signature.clone(),
+            amount_lamports: attempt.amount_lamports,
+            status_summary: "Not the winner".to_string(),
+            last_known_slot,
+            commitment_times: None,
+        };
+        self.0.non_winners.push(non_winner);
+        self
+    }
+
+    pub fn build(self) -> RaceRecord {
+        self.0
+    }
+
+    fn attempt_named(&self, name: &str) -> &SendAttemptRecord {
+        self.0
+            .send_attempts
+            .iter()
+            .find(|a| a.endpoint_name == name)
+            .expect("winners and non-winners are added after their attempt")
+    }
+}
+
+pub fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+impl RaceRecord {
+    pub fn from_outcome(outcome: &RaceOutcome, config_fingerprint: String) -> Self {
+        let mut record = RaceRecord {
+            id: unix_millis(outcome.started_at),
+            config_fingerprint,
+            send_attempts: outcome
+                .send_attempts
+                .iter()
+                .map(|sa| SendAttemptRecord {
+                    endpoint_name: sa.endpoint.name.clone(),
+                    signature: sa.original_signature.to_string(),
+                    amount_lamports: sa.amount_lamports,
+                    error: sa.send_result.as_ref().err().cloned(),
+                    send_started_at_ms: unix_millis(sa.send_start_time),
+                    send_duration_ms: sa.send_duration_ms as u64,
+                    compute_unit_limit: sa.compute_budget.unit_limit,
+                    compute_unit_price_micro_lamports: sa.compute_budget.unit_price_micro_lamports,
+                    fee_lamports: Some(sa.fee_lamports),
+                    send_path: Some(sa.endpoint.send_path.kind().to_string()),
+                    bundle_id: sa.bundle_id.clone(),
+                    bundle_status: sa.bundle_status.clone(),
+                    send_options: sa.send_options,
+                    rebroadcasts: sa.rebroadcasts,
+                    landed_send: sa.landed_send,
+                    warm_up_ms: sa.warm_up_ms.map(|ms| ms as u64),
+                    warm_up_error: sa.warm_up_error.clone(),
+                    timing: sa.timing,
+                    connection_timing: sa.connection_timing,
+                    position: Some(sa.position as u32),
+                    dispatch_slot: sa.dispatch_slot,
+                })
+                .collect(),
+            winner: outcome.winner.as_ref().map(|w| WinnerRecord {
+                endpoint_name: w.endpoint_name.clone(),
+                signature: w.signature.to_string(),
+                amount_lamports: w.amount_lamports,
+                time_to_confirm_ms: Some(w.time_to_confirm_ms as u64),
+                slot: w.slot,
+                confirmation_status: w.confirmation_status_description.clone(),
+                detection_method: Some(w.detection),
+                observer: w.observer.clone(),
+                sender_view_ms: w.sender_view_ms.map(|ms| ms as u64),
+                commitment_times: Some(w.commitment_times),
+            }),
+            first_observed: None,
+            non_winners: outcome
+                .non_winners
+                .iter()
+                .map(|nw| NonWinnerRecord {
+                    endpoint_name: nw.endpoint_name.clone(),
+                    signature: nw.original_signature.to_string(),
+                    amount_lamports: nw.amount_lamports,
+                    status_summary: nw.status_summary.clone(),
+                    last_known_slot: nw.last_known_slot,
+                    commitment_times: nw.commitment_times,
+                })
+                .collect(),
+            fee_selection: outcome.fee_selection.clone(),
+            dispatch_skew_us: outcome.dispatch_skew.map(|skew| skew.as_micros() as u64),
+            unfair: outcome.unfair,
+            mixed_dispatch_reference: outcome.mixed_dispatch_reference,
+            dispatch_order: Some(outcome.dispatch_order.clone()),
+            on_chain_order: outcome.on_chain_order.clone(),
+            slot_timeline: Some(outcome.slot_timeline.clone()),
+            finalized_tracked: outcome.finalized_tracked,
+        };
+        record.settle_on_chain_winner();
+        record
+    }
+
+    /// Makes the transaction that succeeded on chain the winner when it is not
+    /// the one observed first, which is kept as `first_observed`.
+    pub(crate) fn settle_on_chain_winner(&mut self) {
+        let Some(on_chain) = self.on_chain_order.as_ref().and_then(|o| o.winner()) else {
+            return;
+        };
+        if self
+            .winner
+            .as_ref()
+            .is_some_and(|w| w.signature == on_chain.signature)
+        {
+            return;
+        }
+        let Some(index) = self
+            .non_winners
+            .iter()
+            .position(|nw| nw.signature == on_chain.signature)
+        else {
+            return;
+        };
+        let slot = on_chain.slot;
+        let on_chain = self.non_winners.remove(index);
+        // The highest commitment it was seen at before monitoring ended.
+        let reached = on_chain.commitment_times.and_then(|times| {
+            Commitment::ALL
+                .iter()
+                .rev()
+                .find_map(|&level| times.get(level).map(|ms| (level, ms)))
+        });
+        let observed = self.winner.take();
+        self.winner = Some(WinnerRecord {
+            endpoint_name: on_chain.endpoint_name,
+            signature: on_chain.signature,
+            amount_lamports: on_chain.amount_lamports,
+            time_to_confirm_ms: reached.map(|(_, ms)| ms),
+            slot,
+            confirmation_status: match reached {
+                Some((level, _)) => format!("{:?}", level),
+                None => "Succeeded on chain".to_string(),
+            },
+            detection_method: observed.as_ref().and_then(|w| w.detection_method),
+            observer: None,
+            sender_view_ms: None,
+            commitment_times: on_chain.commitment_times,
+        });
+        self.first_observed = observed;
+    }
+
+    /// The transaction observed reaching the target commitment first, whether
+    /// or not it won on chain.
+    pub fn observed_winner(&self) -> Option<&WinnerRecord> {
+        self.first_observed.as_ref().or(self.winner.as_ref())
+    }
+
+    /// Signature of the race's winner: the transaction that succeeded on chain
+    /// when the blocks could be read, otherwise the first one observed confirmed.
+    /// Races stored before the on-chain winner was settled kept the observed one
+    /// as `winner`.
+    pub fn winning_signature(&self) -> Option<&str> {
+        match self.on_chain_order.as_ref().and_then(|o| o.winner()) {
+            Some(on_chain) => Some(on_chain.signature.as_str()),
+            None => self.winner.as_ref().map(|w| w.signature.as_str()),
+        }
+    }
+
+    /// Whether the on-chain winner is not the transaction observed confirming first.
+    pub fn order_disagrees(&self) -> bool {
+        match (
+            self.on_chain_order.as_ref().and_then(|o| o.winner()),
+            self.observed_winner(),
+        ) {
+            (Some(on_chain), Some(observed)) => on_chain.signature != observed.signature,
+            _ => false,
+        }
+    }
+
+    /// Slot the transaction landed in: its block when located on chain,
+    /// otherwise the slot its status was last reported in.
+    pub fn landing_slot(&self, signature: &str) -> Option<u64> {
+        if let Some(position) = self
+            .on_chain_order
+            .as_ref()
+            .and_then(|o| o.position_of(signature))
+        {
+            return Some(position.slot);
+        }
+        if let Some(winner) = [&self.winner, &self.first_observed]
+            .into_iter()
+            .flatten()
+            .find(|w| w.signature == signature)
+        {
+            return Some(winner.slot);
+        }
+        self.non_winners
+            .iter()
+            .find(|nw| nw.signature == signature)
+            .and_then(|nw| nw.last_known_slot)
+    }
+
+    /// Slots between dispatch and landing for one send attempt.
+    pub fn slots_to_land(&self, attempt: &SendAttemptRecord) -> Option<u64> {
+        let dispatch_slot = attempt.dispatch_slot?;
+        self.landing_slot(&attempt.signature)
+            .map(|landed| landed.saturating_sub(dispatch_slot))
+    }
+
+    /// Time from dispatch until the slot clock moved past the attempt's
+    /// landing slot; unlike observed confirmation times, it does not depend
+    /// on the polling interval.
+    pub fn estimated_land_ms(&self, attempt: &SendAttemptRecord) -> Option<u64> {
+        let landing_slot = self.landing_slot(&attempt.signature)?;
+        let ended_at = self
+            .slot_timeline
+            .as_ref()?
+            .slot_ended_at_ms(landing_slot)?;
+        Some(ended_at.saturating_sub(attempt.send_started_at_ms))
+    }
+
+    pub fn started_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(self.id as i64).unwrap_or_default()
+    }
+
+    pub fn involves_endpoint(&self, endpoint_name: &str) -> bool {
+        self.send_attempts
+            .iter()
+            .any(|sa| sa.endpoint_name == endpoint_name)
+    }
+}
+
+/// Appends one race to the JSONL history file, creating it if needed.
+pub fn append_race(path: &Path, record: &RaceRecord) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open history file '{}': {}", path.display(), e))?;
+    let line = serde_json::to_string(record)
+        .map_err(|e| format!("Failed to serialize race {}: {}", record.id, e))?;
+    writeln!(file, "{}", line)
+        .map_err(|e| format!("Failed to write history file '{}': {}", path.display(), e))
+}
+
+/// Loads every race from the history file. A missing file is an empty history.
+pub fn load_races(path: &Path) -> Result<Vec<RaceRecord>, String> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(format!(
+                "Failed to open history file '{}': {}",
+                path.display(),
+                e
+            ))
+        }
+    };
+
+    let mut races = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line =
+            line.map_err(|e| format!("Failed to read history file '{}': {}", path.display(), e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line).map_err(|e| {
+            format!(
+                "Failed to parse line {} of history file '{}': {}",
+                i + 1,
+                path.display(),
+                e
+            )
+        })?;
+        races.push(record);
+    }
+    Ok(races)
+}
+
+/// Selects stored races by participating endpoint and start time.
+#[derive(Debug, Default)]
+pub struct HistoryFilter {
+    /// Keep races that involved any of these endpoints; empty keeps all.
+    pub endpoints: Vec<String>,
+    /// Inclusive lower bound on the race start.
+    pub since: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on the race start.
+    pub until: Option<DateTime<Utc>>,
+    /// Keep races flagged as unfair because of their dispatch skew.
+    pub include_unfair: bool,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, record: &RaceRecord) -> bool {
+        let started_at = record.started_at();
+        (self.include_unfair || !record.unfair)
+            && self.since.is_none_or(|since| started_at >= since)
+            && self.until.is_none_or(|until| started_at < until)
+            && (self.endpoints.is_empty()
+                || self
+                    .endpoints
+                    .iter()
+                    .any(|name| record.involves_endpoint(name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ordering::OnChainPosition;
+    use chrono::TimeZone;
+    use tempfile::tempdir;
+
+    fn on_chain(positions: &[(&str, u64, bool)]) -> OnChainOrder {
+        OnChainOrder {
+            positions: positions
+                .iter()
+                .enumerate()
+                .map(|(index, &(signature, slot, succeeded))| OnChainPosition {
+                    signature: signature.to_string(),
+                    slot,
+                    index,
+                    succeeded,
+                })
+                .collect(),
+            unavailable_slots: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_append_and_load_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+
+        assert!(load_races(&path).unwrap().is_empty());
+        append_race(&path, &RaceBuilder::new(1).attempt("a").build()).unwrap();
+        append_race(
+            &path,
+            &RaceBuilder::new(2).attempt("a").attempt("b").build(),
+        )
+        .unwrap();
+
+        let races = load_races(&path).unwrap();
+        assert_eq!(races.len(), 2);
+        assert_eq!(races[1].id, 2);
+        assert_eq!(races[1].send_attempts[1].endpoint_name, "b");
+    }
+
+    #[test]
+    fn test_filter_by_endpoint_and_date_range() {
+        let day = |d: u32| Utc.with_ymd_and_hms(2026, 1, d, 0, 0, 0).unwrap();
+        let early = RaceBuilder::new(day(1).timestamp_millis() as u64)
+            .attempt("a")
+            .build();
+        let late = RaceBuilder::new(day(3).timestamp_millis() as u64)
+            .attempt("a")
+            .attempt("b")
+            .build();
+
+        let by_endpoint = HistoryFilter {
+            endpoints: vec!["b".to_string()],
+            ..Default::default()
+        };
+        assert!(!by_endpoint.matches(&early));
+        assert!(by_endpoint.matches(&late));
+
+        let by_date = HistoryFilter {
+            since: Some(day(2)),
+            until: Some(day(4)),
+            ..Default::default()
+        };
+        assert!(!by_date.matches(&early));
+        assert!(by_date.matches(&late));
+    }
+
+    #[test]
+    fn test_filter_excludes_unfair_races_unless_asked() {
+        let mut race = RaceBuilder::new(1).attempt("a").build();
+        race.unfair = true;
+
+        assert!(!HistoryFilter::default().matches(&race));
+        assert!(HistoryFilter {
+            include_unfair: true,
+            ..Default::default()
+        }
+        .matches(&race));
+    }
+
+    #[test]
+    fn test_on_chain_winner_overrides_observed_winner() {
+        let mut race = RaceBuilder::new(1)
+            .attempt("a")
+            .attempt("b")
+            .winner("a", 400)
+            .build();
+        assert_eq!(race.winning_signature(), Some("sig-1-a"));
+        assert!(!race.order_disagrees());
+
+        race.on_chain_order = Some(on_chain(&[("sig-1-b", 10, true), ("sig-1-a", 10, false)]));
+        assert_eq!(race.winning_signature(), Some("sig-1-b"));
+        assert!(race.order_disagrees());
+    }
+
+    #[test]
+    fn test_settling_makes_the_on_chain_winner_the_winner() {
+        let mut race = RaceBuilder::new(1)
+            .attempt("a")
+            .attempt("b")
+            .winner("a", 400)
+            .non_winner("b", None)
+            .build();
+        race.non_winners[0].commitment_times = Some(CommitmentTimes {
+            processed_ms: Some(350),
+            ..Default::default()
+        });
+        race.on_chain_order = Some(on_chain(&[("sig-1-b", 10, true)]));
+
+        race.settle_on_chain_winner();
+
+        let winner = race.winner.as_ref().unwrap();
+        assert_eq!(winner.endpoint_name, "b");
+        assert_eq!(winner.slot, 10);
+        assert_eq!(winner.time_to_confirm_ms, Some(350));
+        assert_eq!(winner.confirmation_status, "Processed");
+        assert_eq!(race.first_observed.as_ref().unwrap().endpoint_name, "a");
+        assert!(race.non_winners.is_empty());
+        assert!(race.order_disagrees());
+        assert_eq!(race.landing_slot("sig-1-a"), Some(1));
+    }
+
+    #[test]
+    fn test_slots_to_land_prefers_the_on_chain_slot() {
+        let dispatched = |a: &mut SendAttemptRecord| {
+            a.dispatch_slot = Some(100);
+            a.send_started_at_ms = 1_000;
+        };
+        let race = RaceBuilder::new(1).attempt_with("a", dispatched).build();
+        assert_eq!(race.slots_to_land(&race.send_attempts[0]), None);
+
+        let mut race = RaceBuilder::new(1)
+            .attempt_with("a", dispatched)
+            .non_winner("a", Some(104))
+            .build();
+        assert_eq!(race.slots_to_land(&race.send_attempts[0]), Some(4));
+
+        race.on_chain_order = Some(on_chain(&[("sig-1-a", 102, false)]));
+        assert_eq!(race.slots_to_land(&race.send_attempts[0]), Some(2));
+
+        race.slot_timeline = Some(SlotTimeline {
+            source: "observer".to_string(),
+            arrivals: [(102, 1_200), (103, 1_650)]
+                .iter()
+                .map(|&(slot, seen_at_ms)| crate::slot_clock::SlotArrival {
+                    slot,
+                    seen_at_ms: Some(seen_at_ms),
+                    rooted_at_ms: None,
+                })
+                .collect(),
+        });
+        assert_eq!(race.estimated_land_ms(&race.send_attempts[0]), Some(650));
+    }
+}
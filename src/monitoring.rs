@@ -1,102 +1,455 @@
+use crate::endpoint::{redact_client_error, Endpoint};
 use crate::transactions::SendAttempt;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
 use solana_client::{
-    client_error::{ClientError as SolanaClientError, Result as ClientResult},
-    rpc_client::RpcClient,
-    rpc_response::Response,
+    client_error::Result as ClientResult,
+    nonblocking::pubsub_client::PubsubClient,
+    rpc_config::RpcSignatureSubscribeConfig,
+    rpc_response::{Response, RpcSignatureResult},
 };
 use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
 use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     error::Error,
     time::{Duration, Instant},
 };
+use tokio::sync::watch;
 
-/// Holds information about the transaction that was confirmed first.
+/// How confirmations are detected.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionMethod {
+    /// `getSignatureStatuses` every polling interval; timings are quantized to it.
+    #[default]
+    Polling,
+    /// `signatureSubscribe` notifications, timestamped as they arrive.
+    Websocket,
+}
+
+impl DetectionMethod {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DetectionMethod::Polling => "polling",
+            DetectionMethod::Websocket => "websocket",
+        }
+    }
+}
+
+/// Commitment levels a transaction goes through, in order.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Commitment {
+    Processed,
+    #[default]
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    pub const ALL: [Commitment; 3] = [
+        Commitment::Processed,
+        Commitment::Confirmed,
+        Commitment::Finalized,
+    ];
+
+    fn from_status(status: &TransactionConfirmationStatus) -> Self {
+        match status {
+            TransactionConfirmationStatus::Processed => Commitment::Processed,
+            TransactionConfirmationStatus::Confirmed => Commitment::Confirmed,
+            TransactionConfirmationStatus::Finalized => Commitment::Finalized,
+        }
+    }
+
+    fn config(&self) -> CommitmentConfig {
+        match self {
+            Commitment::Processed => CommitmentConfig::processed(),
+            Commitment::Confirmed => CommitmentConfig::confirmed(),
+            Commitment::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+}
+
+/// Milliseconds from dispatch until a transaction was seen at each commitment,
+/// and how each was detected: polled times are quantized to the polling interval.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommitmentTimes {
+    pub processed_ms: Option<u64>,
+    pub confirmed_ms: Option<u64>,
+    pub finalized_ms: Option<u64>,
+    /// `None` in races stored before the method was recorded per commitment.
+    #[serde(default)]
+    pub processed_via: Option<DetectionMethod>,
+    #[serde(default)]
+    pub confirmed_via: Option<DetectionMethod>,
+    #[serde(default)]
+    pub finalized_via: Option<DetectionMethod>,
+}
+
+impl CommitmentTimes {
+    pub fn get(&self, commitment: Commitment) -> Option<u64> {
+        match commitment {
+            Commitment::Processed => self.processed_ms,
+            Commitment::Confirmed => self.confirmed_ms,
+            Commitment::Finalized => self.finalized_ms,
+        }
+    }
+
+    pub fn detected_via(&self, commitment: Commitment) -> Option<DetectionMethod> {
+        match commitment {
+            Commitment::Processed => self.processed_via,
+            Commitment::Confirmed => self.confirmed_via,
+            Commitment::Finalized => self.finalized_via,
+        }
+    }
+
+    /// Records reaching `commitment` after `ms`, detected `via`. Lower levels
+    /// that were not seen separately, e.g. between two polls, are taken as
+    /// reached then too.
+    pub fn reach(&mut self, commitment: Commitment, ms: u64, via: DetectionMethod) {
+        for level in Commitment::ALL {
+            let (time, method) = match level {
+                Commitment::Processed => (&mut self.processed_ms, &mut self.processed_via),
+                Commitment::Confirmed => (&mut self.confirmed_ms, &mut self.confirmed_via),
+                Commitment::Finalized => (&mut self.finalized_ms, &mut self.finalized_via),
+            };
+            if level <= commitment && time.is_none() {
+                *time = Some(ms);
+                *method = Some(via);
+            }
+        }
+    }
+}
+
+/// The `monitoring` configuration section.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct MonitoringConfig {
+    /// With `websocket`, transactions whose endpoint websocket cannot be
+    /// subscribed to are polled instead.
+    pub method: DetectionMethod,
+    /// Commitment a transaction must reach to win the race.
+    pub target_commitment: Commitment,
+    /// Below a `finalized` target, keep following the winner until it
+    /// finalizes or the monitoring timeout passes, which adds ~13s or more.
+    pub await_finalized: bool,
+}
+
+impl MonitoringConfig {
+    /// Whether monitoring follows the winner until it finalizes.
+    pub fn tracks_finalized(&self) -> bool {
+        self.await_finalized || self.target_commitment == Commitment::Finalized
+    }
+}
+
+/// Holds information about the transaction that reached the target commitment first.
 #[derive(Debug, Clone)]
 pub struct WinningTransactionInfo {
     pub signature: Signature,
-    pub rpc_url: String,
+    pub endpoint_name: String,
     pub amount_lamports: u64,
+    /// Time from dispatch to the target commitment.
     pub time_to_confirm_ms: u128,
+    /// When the transaction was first seen on chain, at any commitment.
+    pub first_seen_at: Instant,
     pub slot: u64,
     pub confirmation_status_description: String,
+    /// How the confirmation was detected, and so how precise its timing is.
+    pub detection: DetectionMethod,
+    /// Observer endpoint that saw the confirmation; `None` when the sending
+    /// endpoint was asked, as it is without observers.
+    pub observer: Option<String>,
+    /// Time to confirm as reported by the sending endpoint itself, when
+    /// observers decided the race.
+    pub sender_view_ms: Option<u128>,
+    /// Every commitment the transaction was seen reaching.
+    pub commitment_times: CommitmentTimes,
 }
 
 /// Holds the final observed status of a transaction that did not win the race.
 #[derive(Debug, Clone)]
 pub struct NonWinningTransactionOutcome {
     pub original_signature: Signature,
-    pub rpc_url: String,
+    pub endpoint_name: String,
     pub amount_lamports: u64,
     pub status_summary: String,
     pub last_known_slot: Option<u64>,
+    /// Known when the transaction also reached the target commitment.
+    pub commitment_times: Option<CommitmentTimes>,
 }
 
 /// Errors that can occur while tracking a single transaction's confirmation status.
 #[derive(Debug)]
 #[allow(dead_code)]
 enum TrackError {
-    RpcError(SolanaClientError),
+    /// The redacted client error.
+    RpcError(String),
     TransactionFailedOnChain(solana_sdk::transaction::TransactionError),
 }
 
-/// Tracks a single transaction until it's confirmed or a permanent error occurs for this path.
+/// Tracks a single transaction, as seen by `vantage`, until it reaches
+/// `target` or a permanent error occurs for this path.
 async fn track_single_transaction(
     attempt_to_track: SendAttempt,
+    vantage: Endpoint,
     poll_interval: Duration,
+    method: DetectionMethod,
+    target: Commitment,
 ) -> Result<WinningTransactionInfo, TrackError> {
     println!(
-        "Tracking Tx: {} on RPC: {}",
-        attempt_to_track.original_signature, attempt_to_track.rpc_url
-    );
-    let client = RpcClient::new_with_commitment(
-        attempt_to_track.rpc_url.clone(),
-        CommitmentConfig::confirmed(),
+        "Tracking Tx: {} sent via RPC: {} on {} ({})",
+        attempt_to_track.original_signature,
+        attempt_to_track.endpoint.name,
+        vantage.name,
+        method.label()
     );
+    if method == DetectionMethod::Websocket {
+        match track_via_websocket(&attempt_to_track, &vantage, target).await {
+            Ok(result) => return result,
+            Err(e) => eprintln!(
+                "Websocket tracking of Tx {} on {} unavailable, falling back to polling: {}",
+                attempt_to_track.original_signature, vantage.name, e
+            ),
+        }
+    }
+    track_via_polling(attempt_to_track, &vantage, poll_interval, target).await
+}
 
+/// Waits for `signatureSubscribe` notifications at every commitment up to
+/// `target`, timestamping each as it arrives.
+///
+/// The outer error means the websocket could not be used and the caller
+/// should poll instead, including when the connection is not established
+/// within the vantage's request timeout. Commitments reached before the
+/// subscriptions were set up are caught by one status check right after
+/// subscribing.
+async fn track_via_websocket(
+    attempt: &SendAttempt,
+    vantage: &Endpoint,
+    target: Commitment,
+) -> Result<Result<WinningTransactionInfo, TrackError>, String> {
+    let websocket_url = vantage.websocket_url();
+    let pubsub = tokio::time::timeout(vantage.timeout(), PubsubClient::new(&websocket_url))
+        .await
+        .map_err(|_| {
+            format!(
+                "websocket connection timed out after {} ms",
+                vantage.timeout().as_millis()
+            )
+        })?
+        .map_err(|e| e.to_string())?;
+    let mut subscriptions = Vec::new();
+    let mut unsubscribes = Vec::new();
+    for level in Commitment::ALL.into_iter().filter(|level| *level <= target) {
+        let (notifications, unsubscribe) = pubsub
+            .signature_subscribe(
+                &attempt.original_signature,
+                Some(RpcSignatureSubscribeConfig {
+                    commitment: Some(level.config()),
+                    enable_received_notification: Some(false),
+                }),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        subscriptions.push(notifications.map(move |notification| (level, notification)));
+        unsubscribes.push(unsubscribe);
+    }
+    let mut notifications = futures_util::stream::select_all(subscriptions);
+
+    let mut commitment_times = CommitmentTimes::default();
+    let mut first_seen_at = None;
+    let client = vantage.nonblocking_rpc_client(CommitmentConfig::confirmed());
+    let current_status = match client
+        .get_signature_statuses(&[attempt.original_signature])
+        .await
+    {
+        Ok(response) => response.value.into_iter().next().flatten(),
+        Err(_) => None,
+    };
+    let mut result = None;
+    if let Some(status) = current_status {
+        let checked_at = Instant::now();
+        if let Some(tx_error) = status.err {
+            result = Some(Err(TrackError::TransactionFailedOnChain(tx_error)));
+        } else if let Some(cs) = &status.confirmation_status {
+            let level = Commitment::from_status(cs);
+            first_seen_at = Some(checked_at);
+            commitment_times.reach(
+                level,
+                ms_since_dispatch(attempt, checked_at),
+                DetectionMethod::Polling,
+            );
+            if level >= target {
+                result = Some(Ok(winning_info(
+                    attempt,
+                    checked_at,
+                    checked_at,
+                    status.slot,
+                    format!("{:?}", cs),
+                    DetectionMethod::Polling,
+                    commitment_times,
+                )));
+            }
+        }
+    }
+    while result.is_none() {
+        let Some((level, notification)) = notifications.next().await else {
+            return Err("subscription closed before a notification".to_string());
+        };
+        let arrived_at = Instant::now();
+        match notification.value {
+            RpcSignatureResult::ProcessedSignature(processed) => match processed.err {
+                Some(tx_error) => {
+                    result = Some(Err(TrackError::TransactionFailedOnChain(tx_error)));
+                }
+                None => {
+                    let first_seen_at = *first_seen_at.get_or_insert(arrived_at);
+                    commitment_times.reach(
+                        level,
+                        ms_since_dispatch(attempt, arrived_at),
+                        DetectionMethod::Websocket,
+                    );
+                    if level >= target {
+                        result = Some(Ok(winning_info(
+                            attempt,
+                            arrived_at,
+                            first_seen_at,
+                            notification.context.slot,
+                            format!("{:?}", level),
+                            DetectionMethod::Websocket,
+                            commitment_times,
+                        )));
+                    }
+                }
+            },
+            RpcSignatureResult::ReceivedSignature(_) => {
+                return Err("unexpected received-signature notification".to_string())
+            }
+        }
+    }
+    drop(notifications);
+    for unsubscribe in unsubscribes {
+        unsubscribe().await;
+    }
+    let _ = pubsub.shutdown().await;
+    Ok(result.expect("loop exits with a result"))
+}
+
+fn ms_since_dispatch(attempt: &SendAttempt, instant: Instant) -> u64 {
+    instant
+        .saturating_duration_since(attempt.send_start_instant)
+        .as_millis() as u64
+}
+
+fn winning_info(
+    attempt: &SendAttempt,
+    reached_at: Instant,
+    first_seen_at: Instant,
+    slot: u64,
+    confirmation_status_description: String,
+    detection: DetectionMethod,
+    commitment_times: CommitmentTimes,
+) -> WinningTransactionInfo {
+    WinningTransactionInfo {
+        signature: attempt.original_signature,
+        endpoint_name: attempt.endpoint.name.clone(),
+        amount_lamports: attempt.amount_lamports,
+        time_to_confirm_ms: reached_at
+            .saturating_duration_since(attempt.send_start_instant)
+            .as_millis(),
+        first_seen_at,
+        slot,
+        confirmation_status_description,
+        detection,
+        observer: None,
+        sender_view_ms: None,
+        commitment_times,
+    }
+}
+
+/// Polls `getSignatureStatuses` until the transaction reaches `target`.
+async fn track_via_polling(
+    attempt_to_track: SendAttempt,
+    vantage: &Endpoint,
+    poll_interval: Duration,
+    target: Commitment,
+) -> Result<WinningTransactionInfo, TrackError> {
+    let client = vantage.nonblocking_rpc_client(CommitmentConfig::confirmed());
+
+    let mut first_seen_at = None;
+    let mut commitment_times = CommitmentTimes::default();
     loop {
-        let result: ClientResult<Response<Vec<Option<TransactionStatus>>>> =
-            client.get_signature_statuses(&[attempt_to_track.original_signature]);
+        let result: ClientResult<Response<Vec<Option<TransactionStatus>>>> = client
+            .get_signature_statuses(&[attempt_to_track.original_signature])
+            .await;
 
         match result {
             Ok(statuses_response) => {
-                if let Some(Some(status)) = statuses_response.value.get(0) {
+                if let Some(Some(status)) = statuses_response.value.first() {
+                    let polled_at = Instant::now();
+                    let first_seen_at = *first_seen_at.get_or_insert(polled_at);
                     if let Some(tx_error) = &status.err {
                         return Err(TrackError::TransactionFailedOnChain(tx_error.clone()));
                     }
                     if let Some(conf_status) = &status.confirmation_status {
-                        match conf_status {
-                            TransactionConfirmationStatus::Confirmed
-                            | TransactionConfirmationStatus::Finalized => {
-                                let confirmed_at = Instant::now();
-                                let time_to_confirm = confirmed_at
-                                    .saturating_duration_since(attempt_to_track.send_start_instant);
-                                return Ok(WinningTransactionInfo {
-                                    signature: attempt_to_track.original_signature,
-                                    rpc_url: attempt_to_track.rpc_url.clone(),
-                                    amount_lamports: attempt_to_track.amount_lamports,
-                                    time_to_confirm_ms: time_to_confirm.as_millis(),
-                                    slot: status.slot,
-                                    confirmation_status_description: format!("{:?}", conf_status),
-                                });
-                            }
-                            TransactionConfirmationStatus::Processed => { /* Still waiting */ }
+                        let level = Commitment::from_status(conf_status);
+                        commitment_times.reach(
+                            level,
+                            ms_since_dispatch(&attempt_to_track, polled_at),
+                            DetectionMethod::Polling,
+                        );
+                        if level >= target {
+                            return Ok(winning_info(
+                                &attempt_to_track,
+                                polled_at,
+                                first_seen_at,
+                                status.slot,
+                                format!("{:?}", conf_status),
+                                DetectionMethod::Polling,
+                                commitment_times,
+                            ));
                         }
                     }
                 }
             }
-            Err(e) => return Err(TrackError::RpcError(e)),
+            Err(e) => return Err(TrackError::RpcError(redact_client_error(e))),
         }
         tokio::time::sleep(poll_interval).await;
     }
 }
 
+/// How long to keep waiting for the winner's sending endpoint to report its
+/// own view once an observer decided the race.
+const SENDER_VIEW_GRACE_MS: u64 = 2_000;
+
+/// One tracking task's result.
+struct TrackOutcome {
+    signature: Signature,
+    /// Whether the vantage point decides the race: an observer, or the
+    /// sending endpoint when there are no observers.
+    decisive: bool,
+    vantage_name: String,
+    result: Result<WinningTransactionInfo, TrackError>,
+}
+
 /// Monitors transactions and returns the first one confirmed, along with others' final statuses.
+///
+/// With `observers`, every transaction is tracked on each of them and the
+/// first observer to see a confirmation decides the race. The sending
+/// endpoint is asked as well, and the winner's own view is kept as
+/// `sender_view_ms`. Without observers, each sending endpoint is asked.
+///
+/// `race_decided` turns `true` the moment the winner is known at the target
+/// commitment, before the sender-view grace period, the finalization
+/// follow-up and the final status checks.
 pub async fn monitor_for_first_confirmation(
     all_send_attempts: Vec<SendAttempt>,
     overall_timeout: Duration,
     poll_interval: Duration,
+    config: MonitoringConfig,
+    observers: &[Endpoint],
+    race_decided: &watch::Sender<bool>,
 ) -> Result<
     (
         Option<WinningTransactionInfo>,
@@ -111,15 +464,41 @@ pub async fn monitor_for_first_confirmation(
     let mut join_set = tokio::task::JoinSet::new();
     let mut successfully_sent_map = HashMap::<Signature, SendAttempt>::new();
     let mut initially_failed_outcomes = Vec::new();
+    let mut pending_sender_views = HashSet::new();
 
     for attempt in all_send_attempts.iter() {
         if attempt.send_result.is_ok() {
-            join_set.spawn(track_single_transaction(attempt.clone(), poll_interval));
+            let mut vantages: Vec<_> = observers.iter().map(|o| (o.clone(), true)).collect();
+            vantages.push((attempt.endpoint.clone(), observers.is_empty()));
+            for (vantage, decisive) in vantages {
+                if !decisive {
+                    pending_sender_views.insert(attempt.original_signature);
+                }
+                let attempt = attempt.clone();
+                join_set.spawn(async move {
+                    let signature = attempt.original_signature;
+                    let vantage_name = vantage.name.clone();
+                    let result = track_single_transaction(
+                        attempt,
+                        vantage,
+                        poll_interval,
+                        config.method,
+                        config.target_commitment,
+                    )
+                    .await;
+                    TrackOutcome {
+                        signature,
+                        decisive,
+                        vantage_name,
+                        result,
+                    }
+                });
+            }
             successfully_sent_map.insert(attempt.original_signature, attempt.clone());
         } else {
             initially_failed_outcomes.push(NonWinningTransactionOutcome {
                 original_signature: attempt.original_signature,
-                rpc_url: attempt.rpc_url.clone(),
+                endpoint_name: attempt.endpoint.name.clone(),
                 amount_lamports: attempt.amount_lamports,
                 status_summary: format!(
                     "Initial send failed: {}",
@@ -130,6 +509,7 @@ pub async fn monitor_for_first_confirmation(
                         .map_or("Unknown send error", |s| s.as_str())
                 ),
                 last_known_slot: None,
+                commitment_times: None,
             });
         }
     }
@@ -140,97 +520,482 @@ pub async fn monitor_for_first_confirmation(
     }
 
     println!(
-        "Monitoring {} successfully sent transactions...",
-        join_set.len()
+        "Monitoring {} successfully sent transactions{}...",
+        successfully_sent_map.len(),
+        if observers.is_empty() {
+            String::new()
+        } else {
+            format!(" from {} observer(s)", observers.len())
+        }
     );
     let deadline = Instant::now() + overall_timeout;
     let mut winner: Option<WinningTransactionInfo> = None;
     let mut completed_tracking_results =
         HashMap::<Signature, Result<WinningTransactionInfo, TrackError>>::new();
+    let mut sender_views = HashMap::<Signature, u128>::new();
+    let mut wait_until = deadline;
 
-    while !join_set.is_empty() && winner.is_none() && Instant::now() < deadline {
+    // Once a winner is known, keep going only for its sender's own view, and
+    // only for a short grace period.
+    while !join_set.is_empty()
+        && winner
+            .as_ref()
+            .is_none_or(|w| pending_sender_views.contains(&w.signature))
+        && Instant::now() < wait_until
+    {
         tokio::select! {
             biased;
             join_result = join_set.join_next() => {
-                if let Some(res) = join_result {
-                    match res {
-                        Ok(Ok(confirmed_info)) => {
-                            if winner.is_none() || confirmed_info.time_to_confirm_ms < winner.as_ref().unwrap().time_to_confirm_ms {
-                                winner = Some(confirmed_info.clone());
-                            }
-                            completed_tracking_results.insert(confirmed_info.signature, Ok(confirmed_info));
-                        }
-                        Ok(Err(_track_error)) => {}
-                        Err(_join_err) => {}
-                    }
-                } else {
+                let Some(res) = join_result else {
                     break;
+                };
+                let Ok(outcome) = res else {
+                    continue;
+                };
+                if !outcome.decisive {
+                    pending_sender_views.remove(&outcome.signature);
+                    if let Ok(info) = &outcome.result {
+                        sender_views.insert(outcome.signature, info.time_to_confirm_ms);
+                    }
+                    continue;
+                }
+                if let Ok(mut confirmed_info) = outcome.result {
+                    if !observers.is_empty() {
+                        confirmed_info.observer = Some(outcome.vantage_name);
+                    }
+                    if winner.is_none() {
+                        race_decided.send_replace(true);
+                        winner = Some(confirmed_info.clone());
+                        wait_until = wait_until
+                            .min(Instant::now() + Duration::from_millis(SENDER_VIEW_GRACE_MS));
+                    }
+                    completed_tracking_results
+                        .entry(confirmed_info.signature)
+                        .or_insert(Ok(confirmed_info));
                 }
             }
             _ = tokio::time::sleep(Duration::from_millis(100)) => { }
         }
     }
+    if let Some(w) = &mut winner {
+        w.sender_view_ms = sender_views.get(&w.signature).copied();
+    }
 
     join_set.shutdown().await;
     if winner.is_none() && Instant::now() >= deadline {
         println!("Overall monitoring timeout reached.");
     }
 
+    // Keep following the winner until it finalizes, so that all of its
+    // commitment times are known.
+    if let Some(w) = winner
+        .as_mut()
+        .filter(|_| config.await_finalized && config.target_commitment < Commitment::Finalized)
+    {
+        let attempt = successfully_sent_map[&w.signature].clone();
+        let vantage = observers
+            .iter()
+            .find(|o| w.observer.as_ref() == Some(&o.name))
+            .unwrap_or(&attempt.endpoint)
+            .clone();
+        println!(
+            "Waiting for Tx {} to finalize on {}...",
+            w.signature, vantage.name
+        );
+        let finalization = track_single_transaction(
+            attempt,
+            vantage,
+            poll_interval,
+            config.method,
+            Commitment::Finalized,
+        );
+        match tokio::time::timeout(
+            deadline.saturating_duration_since(Instant::now()),
+            finalization,
+        )
+        .await
+        {
+            Ok(Ok(finalized)) => {
+                w.commitment_times.finalized_ms = finalized.commitment_times.finalized_ms;
+                w.commitment_times.finalized_via = finalized.commitment_times.finalized_via;
+            }
+            Ok(Err(e)) => eprintln!(
+                "Tracking finalization of Tx {} failed: {:?}",
+                w.signature, e
+            ),
+            Err(_) => println!(
+                "Tx {} did not finalize within the monitoring timeout.",
+                w.signature
+            ),
+        }
+    }
+
     let mut final_outcomes = initially_failed_outcomes;
+    let mut final_checks = tokio::task::JoinSet::new();
 
     for (sig, sent_attempt) in successfully_sent_map {
-        if winner.as_ref().map_or(false, |w| w.signature == sig) {
+        if winner.as_ref().is_some_and(|w| w.signature == sig) {
             continue;
         }
 
-        let final_status_summary: String;
-        let final_slot: Option<u64>;
-
         if let Some(Ok(confirmed_later_info)) = completed_tracking_results.get(&sig) {
-            final_status_summary = format!(
-                "Confirmed (but not the overall winner at {}ms) - Status: {:?}",
-                confirmed_later_info.time_to_confirm_ms,
-                confirmed_later_info.confirmation_status_description
-            );
-            final_slot = Some(confirmed_later_info.slot);
+            final_outcomes.push(NonWinningTransactionOutcome {
+                original_signature: sig,
+                endpoint_name: sent_attempt.endpoint.name.clone(),
+                amount_lamports: sent_attempt.amount_lamports,
+                status_summary: format!(
+                    "Confirmed (but not the overall winner at {}ms via {}) - Status: {:?}",
+                    confirmed_later_info.time_to_confirm_ms,
+                    confirmed_later_info.detection.label(),
+                    confirmed_later_info.confirmation_status_description
+                ),
+                last_known_slot: Some(confirmed_later_info.slot),
+                commitment_times: Some(confirmed_later_info.commitment_times),
+            });
         } else {
-            let rpc_client = RpcClient::new_with_commitment(
-                sent_attempt.rpc_url.clone(),
-                CommitmentConfig::confirmed(),
-            );
-            match rpc_client.get_signature_statuses(&[sig]) {
-                Ok(response) => {
-                    if let Some(Some(status_detail)) = response.value.get(0) {
-                        final_slot = Some(status_detail.slot);
-                        if let Some(err) = &status_detail.err {
-                            final_status_summary = format!("Failed on-chain: {:?}", err);
-                        } else if let Some(cs) = &status_detail.confirmation_status {
-                            final_status_summary =
-                                format!("Not the winner. Final status: {:?}", cs);
-                        } else {
-                            final_status_summary =
-                                "Not the winner. Status unclear in final check.".to_string();
-                        }
-                    } else {
-                        final_slot = None;
-                        final_status_summary =
-                            "Not the winner. Not found in final check.".to_string();
-                    }
-                }
-                Err(e) => {
-                    final_slot = None;
+            let vantages = if observers.is_empty() {
+                vec![sent_attempt.endpoint.clone()]
+            } else {
+                observers.to_vec()
+            };
+            final_checks.spawn(final_status_check(sig, sent_attempt, vantages));
+        }
+    }
+    while let Some(result) = final_checks.join_next().await {
+        match result {
+            Ok(outcome) => final_outcomes.push(outcome),
+            Err(e) => eprintln!("Final status check task failed: {}", e),
+        }
+    }
+    Ok((winner, final_outcomes))
+}
+
+/// Looks up the last status of a transaction that did not win, asking each
+/// of `vantages` in turn until one answers.
+async fn final_status_check(
+    sig: Signature,
+    sent_attempt: SendAttempt,
+    vantages: Vec<Endpoint>,
+) -> NonWinningTransactionOutcome {
+    let final_status_summary: String;
+    let final_slot: Option<u64>;
+
+    let mut response = Err("no vantage point to ask".to_string());
+    for vantage in &vantages {
+        let rpc_client = vantage.nonblocking_rpc_client(CommitmentConfig::confirmed());
+        match rpc_client.get_signature_statuses(&[sig]).await {
+            Ok(statuses) => {
+                response = Ok(statuses);
+                break;
+            }
+            Err(e) => {
+                let e = redact_client_error(e);
+                eprintln!(
+                    "Final status check of Tx {} on {} failed: {}",
+                    sig, vantage.name, e
+                );
+                response = Err(format!("{}: {}", vantage.name, e));
+            }
+        }
+    }
+    match response {
+        Ok(response) => {
+            if let Some(Some(status_detail)) = response.value.first() {
+                final_slot = Some(status_detail.slot);
+                if let Some(err) = &status_detail.err {
+                    final_status_summary = format!("Failed on-chain: {:?}", err);
+                } else if let Some(cs) = &status_detail.confirmation_status {
+                    final_status_summary = format!("Not the winner. Final status: {:?}", cs);
+                } else {
                     final_status_summary =
-                        format!("Not the winner. RPC error in final check: {}", e);
+                        "Not the winner. Status unclear in final check.".to_string();
                 }
+            } else {
+                final_slot = None;
+                final_status_summary = "Not the winner. Not found in final check.".to_string();
             }
         }
-        final_outcomes.push(NonWinningTransactionOutcome {
-            original_signature: sig,
-            rpc_url: sent_attempt.rpc_url.clone(),
-            amount_lamports: sent_attempt.amount_lamports,
-            status_summary: final_status_summary,
-            last_known_slot: final_slot,
+        Err(e) => {
+            final_slot = None;
+            final_status_summary = format!("Not the winner. RPC error in final check: {}", e);
+        }
+    }
+    NonWinningTransactionOutcome {
+        original_signature: sig,
+        endpoint_name: sent_attempt.endpoint.name.clone(),
+        amount_lamports: sent_attempt.amount_lamports,
+        status_summary: final_status_summary,
+        last_known_slot: final_slot,
+        commitment_times: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint::Endpoint;
+
+    const NOT_FOUND_STATUS: &str =
+        r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":7},"value":[null]}}"#;
+    const CONFIRMED_STATUS: &str = r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":7},"value":[{"slot":7,"confirmations":null,"err":null,"status":{"Ok":null},"confirmationStatus":"confirmed"}]}}"#;
+
+    /// Accepts one websocket connection, answers its `signatureSubscribe`
+    /// requests and, once `subscriptions` are in place, notifies each of them
+    /// in commitment order, `gap` apart. Returns the websocket URL and the
+    /// commitments subscribed to.
+    async fn serve_signature_notifications(
+        subscriptions: usize,
+        gap: Duration,
+    ) -> (String, tokio::sync::mpsc::UnboundedReceiver<String>) {
+        use futures_util::SinkExt;
+        use serde_json::{json, Value};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (commitments_tx, commitments_rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let mut subscribed: Vec<(usize, String)> = Vec::new();
+            while let Some(Ok(message)) = ws.next().await {
+                let Message::Text(text) = message else {
+                    continue;
+                };
+                let request: Value = serde_json::from_str(&text).unwrap();
+                let result = match request["method"].as_str().unwrap() {
+                    "signatureSubscribe" => {
+                        let commitment = request["params"][1]["commitment"].as_str().unwrap();
+                        subscribed.push((subscribed.len(), commitment.to_string()));
+                        let _ = commitments_tx.send(commitment.to_string());
+                        json!(subscribed.len() - 1)
+                    }
+                    _ => json!(true),
+                };
+                let response = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result });
+                ws.send(Message::Text(response.to_string())).await.unwrap();
+
+                if request["method"] == "signatureSubscribe" && subscribed.len() == subscriptions {
+                    for level in ["processed", "confirmed", "finalized"] {
+                        let Some((id, _)) = subscribed.iter().find(|(_, c)| c == level) else {
+                            continue;
+                        };
+                        tokio::time::sleep(gap).await;
+                        let notification = json!({
+                            "jsonrpc": "2.0",
+                            "method": "signatureNotification",
+                            "params": {
+                                "result": { "context": { "slot": 9 }, "value": { "err": null } },
+                                "subscription": id,
+                            },
+                        });
+                        ws.send(Message::Text(notification.to_string()))
+                            .await
+                            .unwrap();
+                    }
+                }
+            }
         });
+        (url, commitments_rx)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_final_status_check_falls_back_to_the_next_observer() {
+        let (url, _requests) = crate::send_path::mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            CONFIRMED_STATUS.to_string(),
+        )]);
+        let down = crate::test_support::unreachable_endpoint("down");
+        let up = Endpoint::from_url("up".to_string(), url);
+        let attempt = SendAttempt::accepted(crate::test_support::unreachable_endpoint("a"));
+
+        let outcome = final_status_check(attempt.original_signature, attempt, vec![down, up]).await;
+
+        assert_eq!(
+            outcome.status_summary,
+            "Not the winner. Final status: Confirmed"
+        );
+        assert_eq!(outcome.last_known_slot, Some(7));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_websocket_tracking_times_each_commitment_transition() {
+        let (url, _requests) = crate::send_path::mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            NOT_FOUND_STATUS.to_string(),
+        )]);
+        let (websocket_url, mut commitments) =
+            serve_signature_notifications(3, Duration::from_millis(100)).await;
+        let mut endpoint = Endpoint::from_url("a".to_string(), url);
+        endpoint.websocket_url = Some(websocket_url);
+        let attempt = SendAttempt::accepted(endpoint);
+
+        let vantage = attempt.endpoint.clone();
+        let info = track_via_websocket(&attempt, &vantage, Commitment::Finalized)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let mut subscribed = Vec::new();
+        while let Ok(commitment) = commitments.try_recv() {
+            subscribed.push(commitment);
+        }
+        assert_eq!(subscribed, vec!["processed", "confirmed", "finalized"]);
+        assert_eq!(info.detection, DetectionMethod::Websocket);
+        assert_eq!(info.slot, 9);
+        assert_eq!(info.confirmation_status_description, "Finalized");
+        let times = info.commitment_times;
+        let (processed, confirmed, finalized) = (
+            times.processed_ms.unwrap(),
+            times.confirmed_ms.unwrap(),
+            times.finalized_ms.unwrap(),
+        );
+        assert!(processed < confirmed && confirmed < finalized);
+        assert!(finalized - processed >= 150);
+        for level in Commitment::ALL {
+            assert_eq!(times.detected_via(level), Some(DetectionMethod::Websocket));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_websocket_tracking_stops_at_the_target_commitment() {
+        let (url, _requests) = crate::send_path::mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            NOT_FOUND_STATUS.to_string(),
+        )]);
+        let (websocket_url, mut commitments) =
+            serve_signature_notifications(2, Duration::from_millis(10)).await;
+        let mut endpoint = Endpoint::from_url("a".to_string(), url);
+        endpoint.websocket_url = Some(websocket_url);
+        let attempt = SendAttempt::accepted(endpoint);
+
+        let vantage = attempt.endpoint.clone();
+        let info = track_via_websocket(&attempt, &vantage, Commitment::Confirmed)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(commitments.recv().await.as_deref(), Some("processed"));
+        assert_eq!(commitments.recv().await.as_deref(), Some("confirmed"));
+        assert_eq!(info.confirmation_status_description, "Confirmed");
+        assert!(info.commitment_times.processed_ms.is_some());
+        assert!(info.commitment_times.confirmed_ms.is_some());
+        assert_eq!(info.commitment_times.finalized_ms, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_websocket_tracking_falls_back_to_polling() {
+        let (url, _requests) = crate::send_path::mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            CONFIRMED_STATUS.to_string(),
+        )]);
+        let endpoint = crate::test_support::endpoint_without_websocket("a", url);
+        let attempt = SendAttempt::accepted(endpoint);
+
+        let vantage = attempt.endpoint.clone();
+        let info = track_single_transaction(
+            attempt,
+            vantage,
+            Duration::from_millis(10),
+            DetectionMethod::Websocket,
+            Commitment::Confirmed,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(info.detection, DetectionMethod::Polling);
+        assert_eq!(info.slot, 7);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_websocket_tracking_falls_back_when_the_connection_hangs() {
+        let (url, _requests) = crate::send_path::mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            CONFIRMED_STATUS.to_string(),
+        )]);
+        // Accepts the connection but never answers the websocket handshake.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let websocket_addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (_stream, _) = listener.accept().await.unwrap();
+            std::future::pending::<()>().await;
+        });
+        let mut endpoint = Endpoint::from_url("a".to_string(), url);
+        endpoint.websocket_url = Some(format!("ws://{}", websocket_addr));
+        endpoint.timeout_ms = Some(200);
+        let attempt = SendAttempt::accepted(endpoint);
+
+        let vantage = attempt.endpoint.clone();
+        let info = tokio::time::timeout(
+            Duration::from_secs(5),
+            track_single_transaction(
+                attempt,
+                vantage,
+                Duration::from_millis(10),
+                DetectionMethod::Websocket,
+                Commitment::Confirmed,
+            ),
+        )
+        .await
+        .expect("tracking should not wait for the websocket handshake")
+        .unwrap();
+
+        assert_eq!(info.detection, DetectionMethod::Polling);
+        assert_eq!(info.slot, 7);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_observer_decides_the_winner() {
+        let (observer_url, _) = crate::send_path::mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            CONFIRMED_STATUS.to_string(),
+        )]);
+        // The sending endpoint never sees its transaction, then goes away.
+        let (sender_url, _) = crate::send_path::mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            r#"{"jsonrpc":"2.0","id":1,"result":{"context":{"slot":7},"value":[null]}}"#
+                .to_string(),
+        )]);
+        let observer = Endpoint::from_url("observer".to_string(), observer_url);
+        let attempt = SendAttempt::accepted(Endpoint::from_url("sender".to_string(), sender_url));
+        let (race_decided, decided) = watch::channel(false);
+
+        let (winner, non_winners) = monitor_for_first_confirmation(
+            vec![attempt],
+            Duration::from_secs(5),
+            Duration::from_millis(10),
+            MonitoringConfig::default(),
+            &[observer],
+            &race_decided,
+        )
+        .await
+        .unwrap();
+
+        assert!(*decided.borrow());
+        let winner = winner.unwrap();
+        assert_eq!(winner.endpoint_name, "sender");
+        assert_eq!(winner.observer.as_deref(), Some("observer"));
+        assert_eq!(winner.sender_view_ms, None);
+        assert!(non_winners.is_empty());
+    }
+
+    #[test]
+    fn test_reaching_a_commitment_fills_skipped_lower_ones() {
+        let mut times = CommitmentTimes::default();
+        times.reach(Commitment::Processed, 300, DetectionMethod::Websocket);
+        times.reach(Commitment::Finalized, 13_000, DetectionMethod::Polling);
+        times.reach(Commitment::Confirmed, 14_000, DetectionMethod::Websocket);
+
+        assert_eq!(
+            times,
+            CommitmentTimes {
+                processed_ms: Some(300),
+                confirmed_ms: Some(13_000),
+                finalized_ms: Some(13_000),
+                processed_via: Some(DetectionMethod::Websocket),
+                confirmed_via: Some(DetectionMethod::Polling),
+                finalized_via: Some(DetectionMethod::Polling),
+            }
+        );
     }
-    Ok((winner, final_outcomes))
 }
@@ -0,0 +1,152 @@
+use crate::accounts::AccountInfo;
+use crate::endpoint::redact_client_error;
+use serde::Deserialize;
+use solana_client::nonblocking;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, hash::Hash, message::Message, nonce::State,
+    pubkey::Pubkey, signature::Keypair, signer::Signer, system_instruction,
+    transaction::Transaction,
+};
+use std::error::Error;
+
+/// Lamports each durable-nonce race transaction transfers, before the per-transaction offset.
+fn default_nonce_transfer_lamports() -> u64 {
+    1_000
+}
+
+/// Settings of the durable-nonce conflict strategy.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DurableNonceConfig {
+    /// Address of an initialized nonce account whose authority is one of the two keypairs.
+    #[serde(deserialize_with = "crate::config::deserialize_pubkey")]
+    pub nonce_account: Pubkey,
+    /// Transaction `i` transfers `amount_lamports + i`, which keeps the signatures distinct.
+    #[serde(default = "default_nonce_transfer_lamports")]
+    pub amount_lamports: u64,
+}
+
+/// The on-chain state of an initialized nonce account.
+#[derive(Debug, Clone)]
+pub struct NonceInfo {
+    pub pubkey: Pubkey,
+    pub authority: Pubkey,
+    /// The stored durable nonce, used in place of a recent blockhash.
+    pub blockhash: Hash,
+    pub lamports: u64,
+}
+
+/// Fetches and decodes a nonce account at `confirmed` commitment.
+pub async fn fetch_nonce(
+    rpc_client: &nonblocking::rpc_client::RpcClient,
+    pubkey: &Pubkey,
+) -> Result<NonceInfo, Box<dyn Error>> {
+    // Fetched directly rather than through `nonce_utils`, whose errors embed
+    // the unredacted client error.
+    let account = rpc_client
+        .get_account_with_commitment(pubkey, CommitmentConfig::confirmed())
+        .await
+        .map_err(|e| {
+            format!(
+                "Failed to fetch nonce account {}: {}",
+                pubkey,
+                redact_client_error(e)
+            )
+        })?
+        .value
+        .ok_or_else(|| format!("Nonce account {} not found", pubkey))?;
+    let data = nonblocking::nonce_utils::data_from_account(&account).map_err(|e| {
+        format!(
+            "Account {} is not an initialized nonce account: {}",
+            pubkey, e
+        )
+    })?;
+    Ok(NonceInfo {
+        pubkey: *pubkey,
+        authority: data.authority,
+        blockhash: data.blockhash(),
+        lamports: account.lamports,
+    })
+}
+
+/// Returns whichever of the two accounts is the nonce authority.
+pub fn nonce_authority<'a>(
+    nonce: &NonceInfo,
+    accounts: [&'a AccountInfo; 2],
+) -> Result<&'a AccountInfo, Box<dyn Error>> {
+    accounts
+        .into_iter()
+        .find(|account| account.pubkey == nonce.authority)
+        .ok_or_else(|| {
+            format!(
+                "The authority of nonce account {} ({}) is neither configured keypair.",
+                nonce.pubkey, nonce.authority
+            )
+            .into()
+        })
+}
+
+/// Builds a transaction that creates a rent-exempt nonce account funded by `authority`.
+pub async fn construct_create_nonce_transaction(
+    authority: &AccountInfo,
+    nonce_keypair: &Keypair,
+    rpc_client: &nonblocking::rpc_client::RpcClient,
+) -> Result<Transaction, Box<dyn Error>> {
+    let lamports = rpc_client
+        .get_minimum_balance_for_rent_exemption(State::size())
+        .await
+        .map_err(redact_client_error)?;
+    let instructions = system_instruction::create_nonce_account(
+        &authority.pubkey,
+        &nonce_keypair.pubkey(),
+        &authority.pubkey,
+        lamports,
+    );
+    let recent_blockhash = rpc_client
+        .get_latest_blockhash()
+        .await
+        .map_err(redact_client_error)?;
+    let message = Message::new(&instructions, Some(&authority.pubkey));
+    let mut transaction = Transaction::new_unsigned(message);
+    transaction.try_sign(&[&authority.keypair, nonce_keypair], recent_blockhash)?;
+    Ok(transaction)
+}
+
+/// Builds a transaction that advances the stored nonce without doing anything else.
+pub async fn construct_advance_nonce_transaction(
+    nonce: &NonceInfo,
+    authority: &AccountInfo,
+    rpc_client: &nonblocking::rpc_client::RpcClient,
+) -> Result<Transaction, Box<dyn Error>> {
+    let instruction = system_instruction::advance_nonce_account(&nonce.pubkey, &authority.pubkey);
+    let recent_blockhash = rpc_client
+        .get_latest_blockhash()
+        .await
+        .map_err(redact_client_error)?;
+    let message = Message::new(&[instruction], Some(&authority.pubkey));
+    let mut transaction = Transaction::new_unsigned(message);
+    transaction.try_sign(&[&authority.keypair], recent_blockhash)?;
+    Ok(transaction)
+}
+
+/// Builds a transaction that withdraws every lamport of the nonce account to its
+/// authority, which closes the account.
+pub async fn construct_close_nonce_transaction(
+    nonce: &NonceInfo,
+    authority: &AccountInfo,
+    rpc_client: &nonblocking::rpc_client::RpcClient,
+) -> Result<Transaction, Box<dyn Error>> {
+    let instruction = system_instruction::withdraw_nonce_account(
+        &nonce.pubkey,
+        &authority.pubkey,
+        &authority.pubkey,
+        nonce.lamports,
+    );
+    let recent_blockhash = rpc_client
+        .get_latest_blockhash()
+        .await
+        .map_err(redact_client_error)?;
+    let message = Message::new(&[instruction], Some(&authority.pubkey));
+    let mut transaction = Transaction::new_unsigned(message);
+    transaction.try_sign(&[&authority.keypair], recent_blockhash)?;
+    Ok(transaction)
+}
@@ -0,0 +1,242 @@
+use crate::endpoint::{redact_client_error, Endpoint};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_config::RpcBlockConfig;
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+use solana_transaction_status::{EncodedTransaction, TransactionDetails, UiConfirmedBlock};
+use std::time::Duration;
+
+/// Attempts at fetching a block that the RPC does not serve yet.
+const BLOCK_FETCH_ATTEMPTS: u32 = 5;
+const BLOCK_FETCH_RETRY_DELAY_MS: u64 = 500;
+
+/// Where a race transaction sits in the block that included it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct OnChainPosition {
+    pub signature: String,
+    pub slot: u64,
+    /// Index of the transaction among the block's transactions.
+    pub index: usize,
+    pub succeeded: bool,
+}
+
+/// The race as the leaders ordered it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct OnChainOrder {
+    /// Included race transactions, in block order.
+    pub positions: Vec<OnChainPosition>,
+    /// Slots whose block could not be fetched, with the error.
+    #[serde(default)]
+    pub unavailable_slots: Vec<(u64, String)>,
+}
+
+impl OnChainOrder {
+    /// The first included transaction that executed successfully. Since the
+    /// race transactions conflict, it is the only one that can.
+    pub fn winner(&self) -> Option<&OnChainPosition> {
+        self.positions.iter().find(|p| p.succeeded)
+    }
+
+    pub fn position_of(&self, signature: &str) -> Option<&OnChainPosition> {
+        self.positions.iter().find(|p| p.signature == signature)
+    }
+}
+
+/// Finds the race transactions among a block's transactions.
+pub fn positions_in_block(
+    block: &UiConfirmedBlock,
+    slot: u64,
+    signatures: &[String],
+) -> Vec<OnChainPosition> {
+    let Some(transactions) = &block.transactions else {
+        return Vec::new();
+    };
+    transactions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, tx)| {
+            let signature = match &tx.transaction {
+                EncodedTransaction::Accounts(list) => list.signatures.first()?.clone(),
+                other => other.decode()?.signatures.first()?.to_string(),
+            };
+            signatures.contains(&signature).then(|| OnChainPosition {
+                signature,
+                slot,
+                index,
+                succeeded: tx.meta.as_ref().is_some_and(|meta| meta.err.is_none()),
+            })
+        })
+        .collect()
+}
+
+/// Fetches the blocks of `slots` through `endpoint` and locates the race
+/// transactions in them.
+pub async fn fetch_on_chain_order(
+    endpoint: &Endpoint,
+    slots: &[u64],
+    signatures: &[Signature],
+) -> OnChainOrder {
+    let client = endpoint.nonblocking_rpc_client(CommitmentConfig::confirmed());
+    let signatures: Vec<String> = signatures.iter().map(|s| s.to_string()).collect();
+    let mut slots = slots.to_vec();
+    slots.sort_unstable();
+    slots.dedup();
+
+    let mut order = OnChainOrder::default();
+    for slot in slots {
+        let config = RpcBlockConfig {
+            encoding: None,
+            transaction_details: Some(TransactionDetails::Accounts),
+            rewards: Some(false),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let mut result = client.get_block_with_config(slot, config).await;
+        // A just-confirmed block can take a moment to be served.
+        for _ in 1..BLOCK_FETCH_ATTEMPTS {
+            if result.is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(BLOCK_FETCH_RETRY_DELAY_MS)).await;
+            result = client.get_block_with_config(slot, config).await;
+        }
+        match result {
+            Ok(block) => order
+                .positions
+                .extend(positions_in_block(&block, slot, &signatures)),
+            Err(e) => order.unavailable_slots.push((slot, redact_client_error(e))),
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_transaction_status::{
+        EncodedTransactionWithStatusMeta, UiAccountsList, UiTransactionStatusMeta,
+    };
+
+    fn block_transaction(signature: &str, succeeded: bool) -> EncodedTransactionWithStatusMeta {
+        let meta: UiTransactionStatusMeta = serde_json::from_value(serde_json::json!({
+            "err": if succeeded { serde_json::Value::Null } else { serde_json::json!("AccountInUse") },
+            "status": if succeeded { serde_json::json!({ "Ok": null }) } else { serde_json::json!({ "Err": "AccountInUse" }) },
+            "fee": 5000,
+            "preBalances": [],
+            "postBalances": [],
+        }))
+        .unwrap();
+        EncodedTransactionWithStatusMeta {
+            transaction: EncodedTransaction::Accounts(UiAccountsList {
+                signatures: vec![signature.to_string()],
+                account_keys: Vec::new(),
+            }),
+            meta: Some(meta),
+            version: None,
+        }
+    }
+
+    #[test]
+    fn test_positions_in_block_finds_race_transactions_in_order() {
+        let block = UiConfirmedBlock {
+            previous_blockhash: String::new(),
+            blockhash: String::new(),
+            parent_slot: 9,
+            transactions: Some(vec![
+                block_transaction("other", true),
+                block_transaction("loser", false),
+                block_transaction("winner", true),
+            ]),
+            signatures: None,
+            rewards: None,
+            num_reward_partitions: None,
+            block_time: None,
+            block_height: None,
+        };
+        let race = ["winner".to_string(), "loser".to_string()];
+
+        let order = OnChainOrder {
+            positions: positions_in_block(&block, 10, &race),
+            unavailable_slots: Vec::new(),
+        };
+
+        assert_eq!(
+            order.positions,
+            vec![
+                OnChainPosition {
+                    signature: "loser".to_string(),
+                    slot: 10,
+                    index: 1,
+                    succeeded: false,
+                },
+                OnChainPosition {
+                    signature: "winner".to_string(),
+                    slot: 10,
+                    index: 2,
+                    succeeded: true,
+                },
+            ]
+        );
+        assert_eq!(order.winner().unwrap().signature, "winner");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fetch_retries_blocks_and_records_unavailable_slots() {
+        let race = Signature::new_unique();
+        let block = UiConfirmedBlock {
+            previous_blockhash: String::new(),
+            blockhash: String::new(),
+            parent_slot: 9,
+            transactions: Some(vec![
+                block_transaction("other", true),
+                block_transaction(&race.to_string(), true),
+            ]),
+            signatures: None,
+            rewards: None,
+            num_reward_partitions: None,
+            block_time: None,
+            block_height: None,
+        };
+        let not_available = || {
+            (
+                "HTTP/1.1 200 OK",
+                r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32004,"message":"Block not available"}}"#
+                    .to_string(),
+            )
+        };
+        // Slot 10 is served on the second try, slot 12 never.
+        let mut responses = vec![
+            not_available(),
+            (
+                "HTTP/1.1 200 OK",
+                serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": block }).to_string(),
+            ),
+        ];
+        responses.extend((0..BLOCK_FETCH_ATTEMPTS).map(|_| not_available()));
+        let (url, requests) = crate::send_path::mock_http::serve(responses);
+        let endpoint = Endpoint::from_url("observer".to_string(), url);
+
+        let order = fetch_on_chain_order(&endpoint, &[12, 10, 12], &[race]).await;
+
+        assert_eq!(
+            order.positions,
+            vec![OnChainPosition {
+                signature: race.to_string(),
+                slot: 10,
+                index: 1,
+                succeeded: true,
+            }]
+        );
+        assert_eq!(order.unavailable_slots.len(), 1);
+        assert_eq!(order.unavailable_slots[0].0, 12);
+        assert!(order.unavailable_slots[0].1.contains("Block not available"));
+        let requested_slots: Vec<u64> = requests
+            .try_iter()
+            .map(|body| {
+                serde_json::from_str::<serde_json::Value>(&body).unwrap()["params"][0]
+                    .as_u64()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(requested_slots, vec![10, 10, 12, 12, 12, 12, 12]);
+    }
+}
@@ -0,0 +1,402 @@
+use crate::accounts::determine_account_roles;
+use crate::bundle::poll_bundle_statuses;
+use crate::config::Config;
+use crate::dispatch_order::DispatchOrder;
+use crate::fees::FeeSelection;
+use crate::monitoring::{
+    monitor_for_first_confirmation, DetectionMethod, NonWinningTransactionOutcome,
+    WinningTransactionInfo,
+};
+use crate::ordering::{fetch_on_chain_order, OnChainOrder};
+use crate::rebroadcast::{rebroadcast_until_stopped, RebroadcastTarget};
+use crate::send_path::WireTransaction;
+use crate::slot_clock::{SlotClock, SlotTimeline};
+use crate::transactions::{
+    construct_conflicting_transactions, dispatch_skew, is_unfair, mixed_dispatch_reference,
+    send_transactions_concurrently, PreparedTransaction, SendAttempt,
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::{
+    error::Error,
+    time::{Duration, SystemTime},
+};
+use tokio::sync::watch;
+
+pub const OVERALL_MONITORING_TIMEOUT_SECONDS: u64 = 30;
+pub const POLLING_INTERVAL_MS: u64 = 1000;
+/// How long to wait for block engines to report bundles after monitoring ended.
+pub const BUNDLE_STATUS_TIMEOUT_SECONDS: u64 = 5;
+
+/// Everything observed during a single live race.
+#[derive(Debug, Clone)]
+pub struct RaceOutcome {
+    pub started_at: SystemTime,
+    pub send_attempts: Vec<SendAttempt>,
+    pub winner: Option<WinningTransactionInfo>,
+    pub non_winners: Vec<NonWinningTransactionOutcome>,
+    pub fee_selection: Option<FeeSelection>,
+    /// Time between the first and the last dispatch.
+    pub dispatch_skew: Option<Duration>,
+    /// Whether the skew exceeded `max_dispatch_skew_us`.
+    pub unfair: bool,
+    /// Whether some paths were timed at the request write and others at the
+    /// start of their submission, so the skew is measured between submission
+    /// starts.
+    pub mixed_dispatch_reference: bool,
+    pub dispatch_order: DispatchOrder,
+    /// Race transactions located in their landing blocks; `None` if none landed.
+    pub on_chain_order: Option<OnChainOrder>,
+    /// Slots seen from just before dispatch until the race was decided.
+    pub slot_timeline: SlotTimeline,
+    /// Whether monitoring followed the winner until it finalized.
+    pub finalized_tracked: bool,
+}
+
+/// The signed transactions of one race, in dispatch order, and how their
+/// priority fee and that order were chosen.
+pub struct PreparedRace {
+    pub transactions: Vec<PreparedTransaction>,
+    pub fee_selection: Option<FeeSelection>,
+    pub dispatch_order: DispatchOrder,
+}
+
+/// Determines the sender/recipient roles from fresh balances and constructs one
+/// conflicting transaction per enabled endpoint, signed with a fresh blockhash.
+///
+/// With `auto_priority_fee` configured, the global compute unit price is
+/// selected from recent prioritization fees first. Endpoints are put in the
+/// configured dispatch order for round `round` (0-based) before construction,
+/// so the order also decides which transaction each endpoint gets.
+pub async fn prepare_race(conf: &Config, round: u32) -> Result<PreparedRace, Box<dyn Error>> {
+    println!("\nDetermining account roles...");
+    let (sender_account, recipient_account) = determine_account_roles(conf)
+        .await
+        .map_err(|e| format!("Error determining account roles: {}", e))?;
+    println!(
+        "Sender: Pubkey {}, Balance: {} lamports",
+        sender_account.pubkey, sender_account.balance
+    );
+    println!(
+        "Recipient: Pubkey {}, Balance: {} lamports",
+        recipient_account.pubkey, recipient_account.balance
+    );
+
+    let enabled_endpoints = conf.enabled_endpoints();
+    let dispatch_order = conf.dispatch_order.order(enabled_endpoints.len(), round);
+    println!(
+        "Dispatch order ({}{}): {}",
+        dispatch_order.mode,
+        dispatch_order
+            .seed
+            .map_or(String::new(), |seed| format!(", seed {}", seed)),
+        dispatch_order
+            .permutation
+            .iter()
+            .map(|&i| enabled_endpoints[i].name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let endpoints: Vec<_> = dispatch_order
+        .permutation
+        .iter()
+        .map(|&i| enabled_endpoints[i].clone())
+        .collect();
+    let rpc_client_for_construction = endpoints
+        .first()
+        .ok_or("No enabled endpoints provided in configuration.")?
+        .nonblocking_rpc_client(CommitmentConfig::confirmed());
+
+    let mut compute_budget = conf.compute_budget;
+    let fee_selection = match &conf.auto_priority_fee {
+        Some(auto) => {
+            println!("\nSelecting priority fee from recent prioritization fees...");
+            let selection = auto
+                .fetch_and_select(
+                    &rpc_client_for_construction,
+                    &sender_account.pubkey,
+                    &recipient_account.pubkey,
+                )
+                .await
+                .map_err(|e| format!("Error fetching recent prioritization fees: {}", e))?;
+            println!(
+                "Selected {} micro-lamports/CU: p{} of {} samples over the last {} slots{}",
+                selection.selected_micro_lamports,
+                auto.percentile,
+                selection.samples.len(),
+                auto.lookback_slots,
+                if selection.capped {
+                    format!(" (capped at {})", auto.max_micro_lamports)
+                } else {
+                    String::new()
+                }
+            );
+            for sample in &selection.samples {
+                println!(
+                    "  Slot {}: {} micro-lamports/CU",
+                    sample.slot, sample.micro_lamports
+                );
+            }
+            compute_budget.unit_price_micro_lamports = Some(selection.selected_micro_lamports);
+            Some(selection)
+        }
+        None => None,
+    };
+
+    println!("\nConstructing conflicting transactions...");
+    let prepared_txs = construct_conflicting_transactions(
+        &sender_account,
+        &recipient_account,
+        &endpoints,
+        compute_budget,
+        &conf.conflict_strategy,
+        &conf.transaction_format,
+        &rpc_client_for_construction,
+    )
+    .await
+    .map_err(|e| format!("Error constructing transactions: {}", e))?;
+
+    if !prepared_txs.is_empty() {
+        println!(
+            "Successfully constructed {} conflicting transactions:",
+            prepared_txs.len()
+        );
+        for (i, tx) in prepared_txs.iter().enumerate() {
+            println!(
+                "  Tx {}: Signature: {}, Amount: {} lamports, Fee: {} lamports, Target RPC: {}",
+                i, tx.signature, tx.amount_lamports, tx.fee_lamports, tx.endpoint.name
+            );
+        }
+    }
+
+    Ok(PreparedRace {
+        transactions: prepared_txs,
+        fee_selection,
+        dispatch_order,
+    })
+}
+
+/// Runs one complete live race: prepares fresh transactions, sends them through
+/// every RPC simultaneously and monitors until the first confirmation or timeout.
+/// `round` (0-based) drives round-robin dispatch order.
+pub async fn run_live_race(conf: &Config, round: u32) -> Result<RaceOutcome, Box<dyn Error>> {
+    let started_at = SystemTime::now();
+    let PreparedRace {
+        transactions: prepared_txs,
+        fee_selection,
+        dispatch_order,
+    } = prepare_race(conf, round).await?;
+    if prepared_txs.is_empty() {
+        return Err("No transactions were constructed.".into());
+    }
+
+    // Rebroadcasts re-send exactly what was sent first, so keep a serialized copy.
+    let mut rebroadcast_targets = Vec::new();
+    if conf.rebroadcast.is_some() {
+        for tx in &prepared_txs {
+            rebroadcast_targets.push(RebroadcastTarget {
+                endpoint: tx.endpoint.clone(),
+                transaction: WireTransaction::serialize(tx)?,
+                last_valid_block_height: tx.last_valid_block_height,
+            });
+        }
+    }
+
+    // Slots are read from a neutral observer when there is one, so no path's
+    // own RPC lag skews its slots to land.
+    let observers = conf.enabled_observers();
+    let slot_source = observers
+        .first()
+        .unwrap_or(&prepared_txs[0].endpoint)
+        .clone();
+    let mut slot_clock = SlotClock::default();
+    if let Err(e) = slot_clock.subscribe(&slot_source).await {
+        eprintln!(
+            "Slot subscription on {} failed, using getSlot only: {}",
+            slot_source.name, e
+        );
+    }
+    // One sample right before dispatch covers a failed or quiet subscription.
+    if let Err(e) = slot_clock.sample(&slot_source).await {
+        eprintln!("getSlot on {} failed: {}", slot_source.name, e);
+    }
+
+    println!("\n--- LIVE RUN: Sending Transactions ---");
+    let mut send_attempts = send_transactions_concurrently(prepared_txs).await;
+    for attempt in send_attempts.iter_mut() {
+        attempt.dispatch_slot = slot_clock.slot_at(attempt.send_start_instant);
+    }
+    let dispatch_slots = send_attempts.iter().filter_map(|a| a.dispatch_slot);
+    match (dispatch_slots.clone().min(), dispatch_slots.max()) {
+        (Some(first), Some(last)) if first == last => {
+            println!("Dispatched in slot {} (per {})", first, slot_source.name)
+        }
+        (Some(first), Some(last)) => println!(
+            "Dispatched in slots {}-{} (per {})",
+            first, last, slot_source.name
+        ),
+        _ => println!(
+            "Slot at dispatch unknown: {} reported no slot",
+            slot_source.name
+        ),
+    }
+    let skew = dispatch_skew(&send_attempts);
+    let mixed_reference = mixed_dispatch_reference(&send_attempts);
+    let unfair = is_unfair(skew, conf.max_dispatch_skew_us);
+    if let Some(skew) = skew {
+        println!(
+            "Dispatch skew between first and last send: {}µs{}{}",
+            skew.as_micros(),
+            if mixed_reference {
+                " (between submission starts: only some paths time the request write)"
+            } else {
+                ""
+            },
+            if unfair {
+                format!(
+                    " (above {}µs, race flagged as unfair)",
+                    conf.max_dispatch_skew_us
+                )
+            } else {
+                String::new()
+            }
+        );
+    }
+    println!("\nTransaction send attempts summary:");
+    let mut successful_sends_count = 0;
+    for (i, attempt) in send_attempts.iter().enumerate() {
+        match &attempt.send_result {
+            Ok(returned_sig) => {
+                successful_sends_count += 1;
+                println!(
+                    "  Attempt {}: Tx (original sig: {}) to RPC {} -> SUCCESS. Returned sig: {}. Send duration: {}ms",
+                    i, attempt.original_signature, attempt.endpoint.name, returned_sig, attempt.send_duration_ms
+                );
+            }
+            Err(e) => {
+                println!(
+                    "  Attempt {}: Tx (original sig: {}) to RPC {} -> FAILED. Error: {}. Send duration: {}ms",
+                    i, attempt.original_signature, attempt.endpoint.name, e, attempt.send_duration_ms
+                );
+            }
+        }
+    }
+    println!(
+        "Finished sending: {} successful, {} failed/skipped.",
+        successful_sends_count,
+        send_attempts.len() - successful_sends_count
+    );
+
+    // Only transactions their path accepted are monitored, so only those are rebroadcast.
+    let (stop_rebroadcasting, rebroadcasting_stopped) = watch::channel(false);
+    let rebroadcasts = conf.rebroadcast.map(|config| {
+        rebroadcast_targets.retain(|target| {
+            send_attempts.iter().any(|attempt| {
+                attempt.original_signature == target.transaction.signature
+                    && attempt.send_result.is_ok()
+            })
+        });
+        println!(
+            "Rebroadcasting {} transaction(s) every {}ms until the race is decided.",
+            rebroadcast_targets.len(),
+            config.interval_ms
+        );
+        tokio::spawn(rebroadcast_until_stopped(
+            rebroadcast_targets,
+            config,
+            rebroadcasting_stopped,
+        ))
+    });
+
+    println!("\n--- LIVE RUN: Monitoring Confirmations ---");
+    let monitoring = monitor_for_first_confirmation(
+        send_attempts.clone(),
+        Duration::from_secs(OVERALL_MONITORING_TIMEOUT_SECONDS),
+        Duration::from_millis(POLLING_INTERVAL_MS),
+        conf.monitoring,
+        &conf.enabled_observers(),
+        &stop_rebroadcasting,
+    )
+    .await;
+    // Monitoring sets it once the race is decided; this covers a timeout or an error.
+    stop_rebroadcasting.send_replace(true);
+    let (winner, non_winners) = monitoring
+        .map_err(|e| format!("An error occurred during transaction monitoring: {}", e))?;
+
+    if let Some(rebroadcasts) = rebroadcasts {
+        let summaries = rebroadcasts
+            .await
+            .map_err(|e| format!("Rebroadcasting failed: {}", e))?;
+        for attempt in send_attempts.iter_mut() {
+            let Some(summary) = summaries
+                .iter()
+                .find(|s| s.signature == attempt.original_signature)
+            else {
+                continue;
+            };
+            attempt.rebroadcasts = summary.rebroadcasts;
+            attempt.landed_send = winner
+                .as_ref()
+                .filter(|w| w.signature == attempt.original_signature)
+                .and_then(|w| {
+                    let resolution = match w.detection {
+                        DetectionMethod::Polling => Duration::from_millis(POLLING_INTERVAL_MS),
+                        DetectionMethod::Websocket => Duration::ZERO,
+                    };
+                    summary.landed_send(w.first_seen_at, resolution)
+                });
+        }
+    }
+
+    let landed_slots: Vec<u64> = winner
+        .iter()
+        .map(|w| w.slot)
+        .chain(non_winners.iter().filter_map(|nw| nw.last_known_slot))
+        .collect();
+    let on_chain_order = match (landed_slots.is_empty(), send_attempts.first()) {
+        (false, Some(first_attempt)) => {
+            println!("\n--- LIVE RUN: Reading On-Chain Order ---");
+            let vantage = observers.first().unwrap_or(&first_attempt.endpoint);
+            let signatures: Vec<_> = send_attempts
+                .iter()
+                .map(|attempt| attempt.original_signature)
+                .collect();
+            let order = fetch_on_chain_order(vantage, &landed_slots, &signatures).await;
+            for (slot, error) in &order.unavailable_slots {
+                eprintln!("Could not read block of slot {}: {}", slot, error);
+            }
+            Some(order)
+        }
+        _ => None,
+    };
+    // The clock ran through monitoring, so it covers the landing slots.
+    slot_clock.stop();
+    let slot_timeline = slot_clock.timeline(&slot_source.name);
+
+    if send_attempts
+        .iter()
+        .any(|attempt| attempt.bundle_id.is_some())
+    {
+        println!("\n--- LIVE RUN: Checking Bundle Statuses ---");
+        poll_bundle_statuses(
+            &mut send_attempts,
+            Duration::from_secs(BUNDLE_STATUS_TIMEOUT_SECONDS),
+            Duration::from_millis(POLLING_INTERVAL_MS),
+        )
+        .await;
+    }
+
+    Ok(RaceOutcome {
+        started_at,
+        send_attempts,
+        winner,
+        non_winners,
+        fee_selection,
+        dispatch_skew: skew,
+        unfair,
+        mixed_dispatch_reference: mixed_reference,
+        dispatch_order,
+        on_chain_order,
+        slot_timeline,
+        finalized_tracked: conf.monitoring.tracks_finalized(),
+    })
+}
@@ -0,0 +1,298 @@
+use crate::endpoint::{redact_client_error, Endpoint};
+use crate::send_path::WireTransaction;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+use std::time::{Duration, Instant};
+use tokio::{sync::watch, time::MissedTickBehavior};
+
+/// Consecutive `getBlockHeight` failures after which a path stops, as its
+/// blockhash expiry can no longer be checked.
+const MAX_BLOCK_HEIGHT_FAILURES: u32 = 3;
+
+fn default_interval_ms() -> u64 {
+    2000
+}
+
+/// Re-sends every path's transaction until the race is decided.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RebroadcastConfig {
+    /// Time between two sends of the same transaction through the same path.
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+}
+
+impl RebroadcastConfig {
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms)
+    }
+}
+
+/// A transaction that was accepted by its path and should be re-sent through it.
+pub struct RebroadcastTarget {
+    pub endpoint: Endpoint,
+    pub transaction: WireTransaction,
+    /// Block height after which the transaction's blockhash has expired;
+    /// `None` for durable nonce transactions, which do not expire.
+    pub last_valid_block_height: Option<u64>,
+}
+
+/// Why a path stopped rebroadcasting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebroadcastStop {
+    /// Monitoring saw the winner confirm, or ended without one.
+    MonitoringEnded,
+    BlockhashExpired,
+    /// The block height could not be fetched several times in a row.
+    BlockHeightUnavailable,
+    /// The path could not be connected again.
+    ConnectFailed,
+}
+
+/// What one path's rebroadcast loop did.
+#[derive(Debug, Clone)]
+pub struct RebroadcastSummary {
+    pub signature: Signature,
+    /// Sends after the initial one, whether or not the path accepted them.
+    pub rebroadcasts: u32,
+    /// Start of every rebroadcast, in order.
+    pub send_instants: Vec<Instant>,
+    pub stop: RebroadcastStop,
+    pub last_error: Option<String>,
+}
+
+impl RebroadcastSummary {
+    /// The send that landed, given when monitoring first saw the transaction
+    /// on chain and how precise that time is: 0 for the initial send, `n` for
+    /// the n-th rebroadcast. It is the latest send that started before that
+    /// sighting; `None` when a send started within `resolution` of it, as the
+    /// transaction may have landed before that send.
+    pub fn landed_send(&self, first_seen_at: Instant, resolution: Duration) -> Option<u32> {
+        let sent_before: Vec<_> = self
+            .send_instants
+            .iter()
+            .take_while(|instant| **instant < first_seen_at)
+            .collect();
+        match sent_before.last() {
+            Some(latest) if first_seen_at.duration_since(**latest) < resolution => None,
+            _ => Some(sent_before.len() as u32),
+        }
+    }
+}
+
+/// Re-sends each target through its own path every `config.interval()` until
+/// `stop` turns `true` or the target's blockhash expires.
+///
+/// Rebroadcasts need no precise timing, so every target is an async task on
+/// the shared runtime rather than a thread of its own.
+pub async fn rebroadcast_until_stopped(
+    targets: Vec<RebroadcastTarget>,
+    config: RebroadcastConfig,
+    stop: watch::Receiver<bool>,
+) -> Vec<RebroadcastSummary> {
+    let handles: Vec<_> = targets
+        .into_iter()
+        .map(|target| tokio::spawn(rebroadcast_target(target, config, stop.clone())))
+        .collect();
+
+    let mut summaries = Vec::with_capacity(handles.len());
+    for handle in handles {
+        match handle.await {
+            Ok(summary) => summaries.push(summary),
+            Err(e) => eprintln!("Rebroadcast task failed: {}", e),
+        }
+    }
+    summaries
+}
+
+async fn rebroadcast_target(
+    target: RebroadcastTarget,
+    config: RebroadcastConfig,
+    mut stop: watch::Receiver<bool>,
+) -> RebroadcastSummary {
+    let mut summary = RebroadcastSummary {
+        signature: target.transaction.signature,
+        rebroadcasts: 0,
+        send_instants: Vec::new(),
+        stop: RebroadcastStop::MonitoringEnded,
+        last_error: None,
+    };
+    // Connecting may block, e.g. to look up the TPU leaders.
+    let endpoint = target.endpoint.clone();
+    let connected = tokio::task::spawn_blocking(move || endpoint.send_path.connect(&endpoint))
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+    let path = match connected {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!(
+                "Rebroadcast for RPC {}: failed to connect: {}",
+                target.endpoint.name, e
+            );
+            summary.stop = RebroadcastStop::ConnectFailed;
+            summary.last_error = Some(e);
+            return summary;
+        }
+    };
+    let rpc_client = target
+        .endpoint
+        .nonblocking_rpc_client(CommitmentConfig::confirmed());
+
+    let mut block_height_failures = 0;
+    let mut ticks = tokio::time::interval_at(
+        tokio::time::Instant::now() + config.interval(),
+        config.interval(),
+    );
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => {}
+            // Also resolves when the sender is gone, which ends the race as well.
+            _ = stop.wait_for(|stopped| *stopped) => break,
+        }
+        if let Some(last_valid_block_height) = target.last_valid_block_height {
+            match rpc_client.get_block_height().await {
+                Ok(block_height) => {
+                    block_height_failures = 0;
+                    if block_height > last_valid_block_height {
+                        summary.stop = RebroadcastStop::BlockhashExpired;
+                        break;
+                    }
+                }
+                Err(e) => {
+                    let e = redact_client_error(e);
+                    block_height_failures += 1;
+                    eprintln!(
+                        "Rebroadcast for RPC {}: failed to get block height ({}/{}): {}",
+                        target.endpoint.name, block_height_failures, MAX_BLOCK_HEIGHT_FAILURES, e
+                    );
+                    if block_height_failures >= MAX_BLOCK_HEIGHT_FAILURES {
+                        summary.stop = RebroadcastStop::BlockHeightUnavailable;
+                        summary.last_error = Some(format!("Failed to get block height: {}", e));
+                        break;
+                    }
+                }
+            }
+        }
+
+        if *stop.borrow() {
+            break;
+        }
+
+        let outcome = path.send_async(&target.transaction).await;
+        summary.rebroadcasts += 1;
+        summary.send_instants.push(outcome.start_instant);
+        if let Err(e) = outcome.result {
+            summary.last_error = Some(e);
+        }
+    }
+
+    println!(
+        "Rebroadcast for RPC {}: {} rebroadcast(s), stopped: {:?}",
+        target.endpoint.name, summary.rebroadcasts, summary.stop
+    );
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_landed_send_is_latest_send_before_first_sighting() {
+        let start = Instant::now();
+        let summary = RebroadcastSummary {
+            signature: Signature::default(),
+            rebroadcasts: 2,
+            send_instants: vec![
+                start + Duration::from_millis(100),
+                start + Duration::from_millis(200),
+            ],
+            stop: RebroadcastStop::MonitoringEnded,
+            last_error: None,
+        };
+
+        let exact = Duration::ZERO;
+        assert_eq!(
+            summary.landed_send(start + Duration::from_millis(50), exact),
+            Some(0)
+        );
+        assert_eq!(
+            summary.landed_send(start + Duration::from_millis(150), exact),
+            Some(1)
+        );
+        assert_eq!(
+            summary.landed_send(start + Duration::from_millis(250), exact),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_landed_send_is_unknown_when_sends_are_closer_than_the_resolution() {
+        let start = Instant::now();
+        let summary = RebroadcastSummary {
+            signature: Signature::default(),
+            rebroadcasts: 2,
+            send_instants: vec![
+                start + Duration::from_millis(100),
+                start + Duration::from_millis(200),
+            ],
+            stop: RebroadcastStop::MonitoringEnded,
+            last_error: None,
+        };
+        let polling = Duration::from_millis(1000);
+
+        assert_eq!(
+            summary.landed_send(start + Duration::from_millis(50), polling),
+            Some(0)
+        );
+        assert_eq!(
+            summary.landed_send(start + Duration::from_millis(1000), polling),
+            None
+        );
+        assert_eq!(
+            summary.landed_send(start + Duration::from_millis(1250), polling),
+            Some(2)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rebroadcast_stops_when_signalled() {
+        let (url, requests) = crate::send_path::mock_http::serve(
+            (0..20)
+                .map(|_| ("HTTP/1.1 200 OK", "ok".to_string()))
+                .collect(),
+        );
+        let mut endpoint =
+            Endpoint::from_url("relay".to_string(), "http://localhost:8899".to_string());
+        endpoint.send_path =
+            crate::send_path::SendPathConfig::HttpRelay(crate::send_path::HttpRelayConfig {
+                url,
+                headers: Default::default(),
+                body_template: "{{signature}}".to_string(),
+            });
+        let (stop, stop_rx) = watch::channel(false);
+        let rebroadcasts = tokio::spawn(rebroadcast_until_stopped(
+            vec![RebroadcastTarget {
+                endpoint,
+                transaction: WireTransaction {
+                    bytes: vec![1, 2, 3],
+                    signature: Signature::default(),
+                    tip: None,
+                },
+                last_valid_block_height: None,
+            }],
+            RebroadcastConfig { interval_ms: 20 },
+            stop_rx,
+        ));
+
+        requests.recv().unwrap();
+        requests.recv().unwrap();
+        stop.send_replace(true);
+        let summaries = rebroadcasts.await.unwrap();
+
+        assert_eq!(summaries.len(), 1);
+        assert!(summaries[0].rebroadcasts >= 2);
+        assert_eq!(summaries[0].stop, RebroadcastStop::MonitoringEnded);
+        assert_eq!(summaries[0].last_error, None);
+    }
+}
@@ -0,0 +1,847 @@
+use crate::fees::FeeSelection;
+use crate::history::{NonWinnerRecord, RaceRecord, SendAttemptRecord, WinnerRecord};
+use crate::monitoring::{Commitment, CommitmentTimes, DetectionMethod};
+use crate::ordering::OnChainOrder;
+use crate::race::OVERALL_MONITORING_TIMEOUT_SECONDS;
+use crate::stats::{percentiles, EndpointStats, PositionStats, Spread};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+
+/// Formats the fee a transaction pays, including its priority fee price if one was set.
+fn format_fee(attempt: Option<&&SendAttemptRecord>) -> String {
+    match attempt.and_then(|sa| sa.fee_lamports.map(|fee| (sa, fee))) {
+        Some((sa, fee)) => match sa.compute_unit_price_micro_lamports {
+            Some(price) => format!("{} lamports ({} µL/CU)", fee, price),
+            None => format!("{} lamports", fee),
+        },
+        None => "Unknown".to_string(),
+    }
+}
+
+/// Formats the 1-based dispatch position of an attempt.
+fn format_position(attempt: Option<&&SendAttemptRecord>) -> String {
+    match attempt.and_then(|sa| sa.position) {
+        Some(position) => (position + 1).to_string(),
+        None => "-".to_string(),
+    }
+}
+
+/// Formats the round trip of the connection warm-up that preceded the send.
+fn format_warm_up(attempt: Option<&&SendAttemptRecord>) -> String {
+    match attempt {
+        Some(sa) => match (sa.warm_up_ms, &sa.warm_up_error) {
+            (Some(ms), None) => format!("{}ms", ms),
+            (Some(ms), Some(_)) => format!("failed after {}ms", ms),
+            _ => "-".to_string(),
+        },
+        None => "Unknown".to_string(),
+    }
+}
+
+/// Formats how many slots passed between dispatch and landing.
+fn format_slots_to_land(race: &RaceRecord, attempt: Option<&&SendAttemptRecord>) -> String {
+    match attempt.and_then(|sa| race.slots_to_land(sa)) {
+        Some(slots) => slots.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+/// Formats the slot clock's estimate of when the transaction landed.
+fn format_estimated_land(race: &RaceRecord, attempt: Option<&&SendAttemptRecord>) -> String {
+    match attempt.and_then(|sa| race.estimated_land_ms(sa)) {
+        Some(ms) => format!("~{}ms", ms),
+        None => "-".to_string(),
+    }
+}
+
+/// Formats the time a transaction took to reach `commitment`.
+fn format_commitment(times: Option<&CommitmentTimes>, commitment: Commitment) -> String {
+    let Some(times) = times else {
+        return "-".to_string();
+    };
+    match (times.get(commitment), times.detected_via(commitment)) {
+        (Some(ms), Some(via)) => format!("{}ms ({})", ms, via.label()),
+        (Some(ms), None) => format!("{}ms", ms),
+        (None, _) => "-".to_string(),
+    }
+}
+
+/// The endpoint name, suffixed with its send path unless it is plain RPC.
+fn format_path_label(endpoint_name: &str, attempt: Option<&&SendAttemptRecord>) -> String {
+    match attempt.and_then(|sa| sa.send_path.as_deref()) {
+        Some(kind) if kind != "rpc" => format!("{} ({})", endpoint_name, kind),
+        _ => endpoint_name.to_string(),
+    }
+}
+
+/// The Tx Status of a winner: its status and observed time, if it was observed.
+fn format_winner_status(w: &WinnerRecord) -> String {
+    match w.time_to_confirm_ms {
+        Some(ms) => format!(
+            "{} ({}ms via {})",
+            w.confirmation_status,
+            ms,
+            w.detection_method.unwrap_or_default().label()
+        ),
+        None => w.confirmation_status.clone(),
+    }
+}
+
+/// Generates a markdown table showing RPC endpoints, transaction statuses, send durations, fees, and full signatures.
+///
+/// The Finalized column is left out when the race did not follow transactions
+/// until finalized.
+pub fn generate_tx_summary_table(race: &RaceRecord) -> String {
+    let finalized = tracks_finalized(race);
+    let mut table = if finalized {
+        String::from(
+            "| RPC | Pos | Tx Status | Processed | Confirmed | Finalized | Slots to Land | Est. Land | Warm-up RTT | Sent Duration | Fee | Tx Full Signature |\n",
+        )
+    } else {
+        String::from(
+            "| RPC | Pos | Tx Status | Processed | Confirmed | Slots to Land | Est. Land | Warm-up RTT | Sent Duration | Fee | Tx Full Signature |\n",
+        )
+    };
+    table.push_str(if finalized {
+        "|---|---|---|---|---|---|---|---|---|---|---|---|\n"
+    } else {
+        "|---|---|---|---|---|---|---|---|---|---|---|\n"
+    });
+
+    let send_map: HashMap<_, _> = race
+        .send_attempts
+        .iter()
+        .map(|sa| (sa.signature.as_str(), sa))
+        .collect();
+
+    let push_row = |table: &mut String,
+                    endpoint_name: &str,
+                    signature: &str,
+                    status: &str,
+                    commitment_times: Option<&CommitmentTimes>| {
+        let sa = send_map.get(signature);
+        let duration_str = match sa {
+            Some(sa) => format!("{}ms", sa.send_duration_ms),
+            None => "Unknown".to_string(),
+        };
+        let finalized_cell = if finalized {
+            format!(
+                " {} |",
+                format_commitment(commitment_times, Commitment::Finalized)
+            )
+        } else {
+            String::new()
+        };
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {} |{} {} | {} | {} | {} | {} | {} |\n",
+            format_path_label(endpoint_name, sa),
+            format_position(sa),
+            status,
+            format_commitment(commitment_times, Commitment::Processed),
+            format_commitment(commitment_times, Commitment::Confirmed),
+            finalized_cell,
+            format_slots_to_land(race, sa),
+            format_estimated_land(race, sa),
+            format_warm_up(sa),
+            duration_str,
+            format_fee(sa),
+            signature
+        ));
+    };
+
+    let winners = race
+        .winner
+        .iter()
+        .map(|w| (w, format!("🏆 {}", format_winner_status(w))))
+        .chain(race.first_observed.iter().map(|w| {
+            (
+                w,
+                format!(
+                    "⚠️ {}, first observed but failed on chain",
+                    format_winner_status(w)
+                ),
+            )
+        }));
+    for (w, status) in winners {
+        push_row(
+            &mut table,
+            &w.endpoint_name,
+            &w.signature,
+            &status,
+            w.commitment_times.as_ref(),
+        );
+    }
+
+    for nw in &race.non_winners {
+        push_row(
+            &mut table,
+            &nw.endpoint_name,
+            &nw.signature,
+            &nw.status_summary,
+            nw.commitment_times.as_ref(),
+        );
+    }
+
+    table
+}
+
+/// Whether the race followed transactions until finalized, or recorded a
+/// finalized time anyway, as races stored before this was tracked may have.
+fn tracks_finalized(race: &RaceRecord) -> bool {
+    race.finalized_tracked
+        || race
+            .winner
+            .iter()
+            .chain(&race.first_observed)
+            .filter_map(|w| w.commitment_times.as_ref())
+            .chain(
+                race.non_winners
+                    .iter()
+                    .filter_map(|nw| nw.commitment_times.as_ref()),
+            )
+            .any(|times| times.get(Commitment::Finalized).is_some())
+}
+
+/// Describes how the priority fee of a race was selected.
+fn format_fee_selection(selection: &FeeSelection) -> String {
+    format!(
+        "Priority fee: {} µL/CU, p{} of {} prioritization fee samples over the last {} slots{}",
+        selection.selected_micro_lamports,
+        selection.config.percentile,
+        selection.samples.len(),
+        selection.config.lookback_slots,
+        if selection.capped {
+            format!(" (capped at {} µL/CU)", selection.config.max_micro_lamports)
+        } else {
+            String::new()
+        }
+    )
+}
+
+/// Formats a phase duration given in microseconds.
+fn format_us(us: u64) -> String {
+    format!("{:.2}ms", us as f64 / 1000.0)
+}
+
+/// Generates a markdown table of the timed phases of every send.
+///
+/// Connection phases are those of the connection the send went over; the
+/// `Connection` column tells whether the warm-up or the send itself opened it.
+pub fn generate_timing_table(race: &RaceRecord) -> String {
+    let mut table = String::from(
+        "| RPC | Connection | DNS | TCP Connect | TLS | Request Write | First Byte | Sent Duration |\n",
+    );
+    table.push_str("|---|---|---|---|---|---|---|---|\n");
+    for sa in &race.send_attempts {
+        let Some(timing) = &sa.timing else {
+            continue;
+        };
+        let (opened_by, connection) = match (timing.dns_us, &sa.connection_timing) {
+            (Some(_), _) => ("send", timing),
+            (None, Some(connection)) => ("warm-up", connection),
+            // Races stored before the warm-up's setup was kept.
+            (None, None) => ("warm-up", timing),
+        };
+        let phase = |us: Option<u64>| match us {
+            Some(us) => format_us(us),
+            None if connection.dns_us.is_none() => "reused".to_string(),
+            None => "-".to_string(),
+        };
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} | {}ms |\n",
+            sa.endpoint_name,
+            opened_by,
+            phase(connection.dns_us),
+            phase(connection.connect_us),
+            phase(connection.tls_us),
+            format_us(timing.write_us),
+            format_us(timing.ttfb_us),
+            sa.send_duration_ms
+        ));
+    }
+    table
+}
+
+fn print_send_timings(race: &RaceRecord) {
+    if race.send_attempts.iter().all(|sa| sa.timing.is_none()) {
+        println!("\nNo send timings recorded; only timed_rpc send paths record them.");
+        return;
+    }
+    println!("\n### Send Timing Breakdown");
+    println!("- Connection: Whether the warm-up or the send opened the connection; DNS, TCP Connect and TLS are its setup");
+    println!("- First Byte: From the end of the request write to the first byte of the response");
+    println!("{}", generate_timing_table(race));
+}
+
+/// Prints the human-readable report of a single live race, with the send
+/// timing breakdown if `show_timings` is set.
+pub fn print_race_report(race: &RaceRecord, show_timings: bool) {
+    if let Some(selection) = &race.fee_selection {
+        println!("\n{}", format_fee_selection(selection));
+    }
+    if let Some(order) = &race.dispatch_order {
+        println!(
+            "\nDispatch order: {}{}",
+            order.mode,
+            order
+                .seed
+                .map_or(String::new(), |seed| format!(" (seed {})", seed))
+        );
+    }
+    if let Some(skew) = race.dispatch_skew_us {
+        println!(
+            "\nDispatch skew (first to last send): {}µs{}{}",
+            skew,
+            if race.mixed_dispatch_reference {
+                " (between submission starts: timed_rpc paths were mixed with paths that cannot time the request write)"
+            } else {
+                ""
+            },
+            if race.unfair {
+                " ⚠️ unfair race, excluded from aggregate statistics"
+            } else {
+                ""
+            }
+        );
+    }
+    match &race.winner {
+        Some(winner) => {
+            println!("\n--- Test Complete: Winner Found! ---");
+            println!("Fastest Transaction Signature: {}", winner.signature);
+            println!("Winning RPC: {}", winner.endpoint_name);
+            println!("Amount Sent: {} lamports", winner.amount_lamports);
+            match winner.time_to_confirm_ms {
+                Some(ms) => println!(
+                    "Time from Send to {}: {} ms (detected via {})",
+                    winner.confirmation_status,
+                    ms,
+                    match winner.detection_method.unwrap_or_default() {
+                        DetectionMethod::Websocket => "signatureSubscribe notification",
+                        DetectionMethod::Polling => "getSignatureStatuses polling",
+                    }
+                ),
+                None => println!(
+                    "Time from Send: not observed before monitoring ended, found in its block"
+                ),
+            }
+            println!("Confirmed in Slot: {}", winner.slot);
+            if let Some(first) = &race.first_observed {
+                println!(
+                    "⚠️ First Observed: {} ({}), but its transaction failed on chain",
+                    first.endpoint_name,
+                    format_winner_status(first)
+                );
+            }
+            let winning_attempt = race
+                .send_attempts
+                .iter()
+                .find(|sa| sa.signature == winner.signature);
+            if let Some(slots) = winning_attempt.and_then(|sa| race.slots_to_land(sa)) {
+                println!("Slots from Dispatch to Landing: {}", slots);
+            }
+            if let (Some(sa), Some(timeline)) = (winning_attempt, &race.slot_timeline) {
+                if let Some(ms) = race.estimated_land_ms(sa) {
+                    match winner.time_to_confirm_ms {
+                        Some(observed) => println!(
+                            "Estimated Land Time: ~{} ms (landing slot ended per {}'s slot clock; {} ms observed)",
+                            ms, timeline.source, observed
+                        ),
+                        None => println!(
+                            "Estimated Land Time: ~{} ms (landing slot ended per {}'s slot clock)",
+                            ms, timeline.source
+                        ),
+                    }
+                }
+                if let Some(rooted_at) = race
+                    .landing_slot(&winner.signature)
+                    .and_then(|slot| timeline.rooted_at_ms(slot))
+                {
+                    println!(
+                        "Landing Slot Rooted: {} ms after dispatch",
+                        rooted_at.saturating_sub(sa.send_started_at_ms)
+                    );
+                }
+            }
+            if let (Some(observer), Some(observed)) = (&winner.observer, winner.time_to_confirm_ms)
+            {
+                println!("Observed by: {} (neutral observer)", observer);
+                match winner.sender_view_ms {
+                    Some(ms) => println!(
+                        "Sender's own view: {} ms ({} ms {} than the observer)",
+                        ms,
+                        ms.abs_diff(observed),
+                        if ms <= observed { "earlier" } else { "later" }
+                    ),
+                    None => println!(
+                        "Sender's own view: not confirmed within the grace period after the observer"
+                    ),
+                }
+            }
+
+            print_transaction_summary(race, show_timings);
+
+            if !race.non_winners.is_empty() {
+                println!("\nSummary of other transactions:");
+                print_non_winning_outcomes(&race.non_winners);
+            }
+        }
+        None => {
+            println!("\n--- Test Complete: No Winner Found ---");
+            println!(
+                "No transaction was confirmed within the timeout of {} seconds.",
+                OVERALL_MONITORING_TIMEOUT_SECONDS
+            );
+            if !race.non_winners.is_empty() {
+                print_transaction_summary(race, show_timings);
+
+                println!("\nSummary of transactions attempted:");
+                print_non_winning_outcomes(&race.non_winners);
+            }
+        }
+    }
+}
+
+/// Prints the legend and the summary table of every transaction, followed by
+/// bundles, rebroadcasts, the on-chain order and, if asked, send timings.
+fn print_transaction_summary(race: &RaceRecord, show_timings: bool) {
+    println!("\nThe following table summarizes all transactions and their outcomes:");
+    println!("- RPC: The RPC endpoint used for sending the transaction");
+    println!("- Pos: Position in the race's dispatch order");
+    if race.winner.is_some() {
+        println!("- Tx Status: Final status of the transaction (🏆 indicates winner, ⚠️ the transaction observed first when it failed on chain)");
+    } else {
+        println!("- Tx Status: Final status of the transaction");
+    }
+    if tracks_finalized(race) {
+        println!("- Processed / Confirmed / Finalized: Time from dispatch until the transaction was seen at each commitment, and how: polled times are quantized to the polling interval");
+    } else {
+        println!("- Processed / Confirmed: Time from dispatch until the transaction was seen at each commitment, and how: polled times are quantized to the polling interval");
+        println!("  (no Finalized column: monitoring stopped before finalization; set monitoring.await_finalized to track it)");
+    }
+    println!("- Slots to Land: Slots between dispatch and the block the transaction landed in");
+    println!("- Est. Land: Time from dispatch until the slot clock moved past the landing slot");
+    println!("- Warm-up RTT: Round trip of the request that opened the connection before dispatch");
+    println!("- Sent Duration: Time taken to send the transaction to the RPC node");
+    println!("- Fee: Signature plus priority fee the transaction pays if it lands");
+    println!("- Tx Full Signature: The complete transaction signature\n");
+    println!("### Transaction Summary Table");
+    println!("{}", generate_tx_summary_table(race));
+    print_bundle_statuses(race);
+    print_rebroadcasts(race);
+    print_on_chain_order(race);
+    if show_timings {
+        print_send_timings(race);
+    }
+}
+
+fn print_bundle_statuses(race: &RaceRecord) {
+    let bundles: Vec<_> = race
+        .send_attempts
+        .iter()
+        .filter_map(|sa| sa.bundle_id.as_ref().map(|id| (sa, id)))
+        .collect();
+    if bundles.is_empty() {
+        return;
+    }
+    println!("\nBundles:");
+    for (sa, bundle_id) in bundles {
+        println!(
+            "  - RPC: {}, Bundle: {}, Status: {}",
+            sa.endpoint_name,
+            bundle_id,
+            sa.bundle_status.as_deref().unwrap_or("Unknown")
+        );
+    }
+}
+
+fn print_rebroadcasts(race: &RaceRecord) {
+    if race
+        .send_attempts
+        .iter()
+        .all(|sa| sa.rebroadcasts == 0 && sa.landed_send.is_none())
+    {
+        return;
+    }
+    println!("\nRebroadcasts:");
+    for sa in &race.send_attempts {
+        let landed = match sa.landed_send {
+            Some(0) => ", landed: initial send".to_string(),
+            Some(n) => format!(", landed: rebroadcast #{}", n),
+            None => String::new(),
+        };
+        println!(
+            "  - RPC: {}, Rebroadcasts: {}{}",
+            sa.endpoint_name, sa.rebroadcasts, landed
+        );
+    }
+}
+
+/// Generates a markdown table of every attempt's place in its landing block,
+/// in block order, followed by the attempts that were not included.
+pub fn generate_on_chain_table(race: &RaceRecord, order: &OnChainOrder) -> String {
+    let mut table = String::from("| RPC | Slot | Index | Result | Tx Full Signature |\n");
+    table.push_str("|---|---|---|---|---|\n");
+    let mut attempts: Vec<_> = race
+        .send_attempts
+        .iter()
+        .map(|sa| (sa, order.position_of(&sa.signature)))
+        .collect();
+    attempts.sort_by_key(|(_, position)| match position {
+        Some(p) => (0, p.slot, p.index),
+        None => (1, 0, 0),
+    });
+    for (sa, position) in attempts {
+        let (slot, index, result) = match position {
+            Some(p) if p.succeeded => (p.slot.to_string(), p.index.to_string(), "✅ Succeeded"),
+            Some(p) => (p.slot.to_string(), p.index.to_string(), "❌ Failed"),
+            None => ("-".to_string(), "-".to_string(), "Not included"),
+        };
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            sa.endpoint_name, slot, index, result, sa.signature
+        ));
+    }
+    table
+}
+
+fn print_on_chain_order(race: &RaceRecord) {
+    let Some(order) = &race.on_chain_order else {
+        return;
+    };
+    println!("\n### On-Chain Order");
+    for (slot, error) in &order.unavailable_slots {
+        println!("Block of slot {} could not be read: {}", slot, error);
+    }
+    println!("{}", generate_on_chain_table(race, order));
+    if race.order_disagrees() {
+        let on_chain = order.winner().map_or("", |p| p.signature.as_str());
+        let endpoint = race
+            .send_attempts
+            .iter()
+            .find(|sa| sa.signature == on_chain)
+            .map_or("unknown", |sa| sa.endpoint_name.as_str());
+        println!(
+            "⚠️ On-chain order disagrees with observed confirmation order: {} succeeded on chain, but {} was observed confirming first.",
+            endpoint,
+            race.observed_winner()
+                .map_or("", |w| w.endpoint_name.as_str())
+        );
+    }
+}
+
+fn print_non_winning_outcomes(outcomes: &[NonWinnerRecord]) {
+    for outcome in outcomes {
+        println!(
+            "  - Sig: {}, RPC: {}, Amount: {} lamports, Status: {}",
+            outcome.signature,
+            outcome.endpoint_name,
+            outcome.amount_lamports,
+            outcome.status_summary
+        );
+        if let Some(slot) = outcome.last_known_slot {
+            println!("    Last known slot: {}", slot);
+        }
+    }
+}
+
+fn format_spread(spread: Option<Spread>) -> String {
+    match spread {
+        Some(s) => format!("{} / {} / {:.0} / {}", s.min, s.median, s.mean, s.max),
+        None => "-".to_string(),
+    }
+}
+
+/// Generates a markdown table of per-RPC results aggregated over several races.
+///
+/// Millisecond and slot columns are rendered as `min / median / mean / max`.
+pub fn generate_aggregate_table(stats: &[EndpointStats]) -> String {
+    let mut table = String::from(
+        "| RPC | Races | Wins | Losses | Send Failures | Win Rate | Confirm ms (min / median / mean / max) | Slots to Land (min / median / mean / max) | Send ms (min / median / mean / max) |\n",
+    );
+    table.push_str("|---|---|---|---|---|---|---|---|---|\n");
+
+    for s in stats {
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {:.1}% | {} | {} | {} |\n",
+            s.endpoint_name,
+            s.races,
+            s.wins,
+            s.losses,
+            s.send_failures,
+            s.win_rate() * 100.0,
+            format_spread(Spread::from_samples(&s.confirm_times_ms)),
+            format_spread(Spread::from_samples(&s.slots_to_land)),
+            format_spread(Spread::from_samples(&s.send_durations_ms)),
+        ));
+    }
+
+    table
+}
+
+/// Generates a markdown table listing stored races, one row per race.
+pub fn generate_history_table(races: &[RaceRecord]) -> String {
+    let mut table = String::from(
+        "| Race | Started (UTC) | Endpoints | Winner | Confirm ms | Slot | Skew µs | Config |\n",
+    );
+    table.push_str("|---|---|---|---|---|---|---|---|\n");
+
+    for race in races {
+        let (winner, confirm_ms, slot) = match &race.winner {
+            Some(w) => (
+                w.endpoint_name.clone(),
+                w.time_to_confirm_ms
+                    .map_or("-".to_string(), |ms| ms.to_string()),
+                w.slot.to_string(),
+            ),
+            None => ("-".to_string(), "-".to_string(), "-".to_string()),
+        };
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} | {} |\n",
+            race.id,
+            race.started_at().format("%Y-%m-%d %H:%M:%S"),
+            race.send_attempts.len(),
+            winner,
+            confirm_ms,
+            slot,
+            match race.dispatch_skew_us {
+                Some(skew) => format!(
+                    "{}{}{}",
+                    skew,
+                    if race.mixed_dispatch_reference {
+                        " (mixed)"
+                    } else {
+                        ""
+                    },
+                    if race.unfair { " ⚠️" } else { "" }
+                ),
+                None => "-".to_string(),
+            },
+            race.config_fingerprint.chars().take(8).collect::<String>(),
+        ));
+    }
+
+    table
+}
+
+fn format_percentiles(samples: &[u64]) -> String {
+    match percentiles(samples, &[50.0, 90.0, 99.0]) {
+        Some(p) => format!("{} / {} / {}", p[0], p[1], p[2]),
+        None => "-".to_string(),
+    }
+}
+
+/// Generates a markdown table of wins per dispatch position.
+pub fn generate_position_table(stats: &[PositionStats]) -> String {
+    let mut table = String::from("| Position | Races | Wins | Win Rate |\n");
+    table.push_str("|---|---|---|---|\n");
+    for s in stats {
+        table.push_str(&format!(
+            "| {} | {} | {} | {:.1}% |\n",
+            s.position + 1,
+            s.races,
+            s.wins,
+            if s.races == 0 {
+                0.0
+            } else {
+                s.wins as f64 * 100.0 / s.races as f64
+            }
+        ));
+    }
+    table
+}
+
+/// Generates a markdown table of per-RPC win rates and latency percentiles.
+pub fn generate_percentile_table(stats: &[EndpointStats]) -> String {
+    let mut table = String::from(
+        "| RPC | Races | Wins | Win Rate | Send Failures | Confirm ms (p50 / p90 / p99) | Slots to Land (p50 / p90 / p99) | Send ms (p50 / p90 / p99) |\n",
+    );
+    table.push_str("|---|---|---|---|---|---|---|---|\n");
+
+    for s in stats {
+        table.push_str(&format!(
+            "| {} | {} | {} | {:.1}% | {} | {} | {} | {} |\n",
+            s.endpoint_name,
+            s.races,
+            s.wins,
+            s.win_rate() * 100.0,
+            s.send_failures,
+            format_percentiles(&s.confirm_times_ms),
+            format_percentiles(&s.slots_to_land),
+            format_percentiles(&s.send_durations_ms),
+        ));
+    }
+
+    table
+}
+
+fn format_balance(balance: &Result<u64, String>) -> String {
+    match balance {
+        Ok(lamports) => format!("{} lamports", lamports),
+        Err(e) => format!("Error: {}", e),
+    }
+}
+
+/// Both keypairs' balances as reported by one RPC endpoint.
+pub struct BalanceRow {
+    pub endpoint_name: String,
+    pub balance_1: Result<u64, String>,
+    pub balance_2: Result<u64, String>,
+}
+
+/// Generates a markdown table of both keypairs' balances per RPC endpoint.
+pub fn generate_balance_table(pubkey_1: &Pubkey, pubkey_2: &Pubkey, rows: &[BalanceRow]) -> String {
+    let mut table = format!(
+        "| RPC | Keypair 1 ({}) | Keypair 2 ({}) |\n",
+        pubkey_1, pubkey_2
+    );
+    table.push_str("|---|---|---|\n");
+
+    for row in rows {
+        table.push_str(&format!(
+            "| {} | {} | {} |\n",
+            row.endpoint_name,
+            format_balance(&row.balance_1),
+            format_balance(&row.balance_2)
+        ));
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::RaceBuilder;
+    use crate::ordering::OnChainPosition;
+
+    fn on_chain(positions: &[(&str, u64, usize, bool)]) -> OnChainOrder {
+        OnChainOrder {
+            positions: positions
+                .iter()
+                .map(|&(signature, slot, index, succeeded)| OnChainPosition {
+                    signature: signature.to_string(),
+                    slot,
+                    index,
+                    succeeded,
+                })
+                .collect(),
+            unavailable_slots: Vec::new(),
+        }
+    }
+
+    /// The cells of each row below the header and separator.
+    fn rows(table: &str) -> Vec<Vec<&str>> {
+        table
+            .lines()
+            .skip(2)
+            .map(|line| line.trim_matches('|').split(" | ").map(str::trim).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_summary_has_a_finalized_column_only_when_tracked() {
+        let mut race = RaceBuilder::new(1)
+            .attempt("a")
+            .attempt("b")
+            .winner("a", 400)
+            .non_winner("b", None)
+            .build();
+        race.winner.as_mut().unwrap().commitment_times = Some(CommitmentTimes {
+            processed_ms: Some(300),
+            confirmed_ms: Some(400),
+            ..Default::default()
+        });
+
+        let table = generate_tx_summary_table(&race);
+        assert!(!table.lines().next().unwrap().contains("Finalized"));
+        assert!(rows(&table).iter().all(|row| row.len() == 11));
+
+        race.finalized_tracked = true;
+        let table = generate_tx_summary_table(&race);
+        assert!(table
+            .lines()
+            .next()
+            .unwrap()
+            .contains("| Confirmed | Finalized |"));
+        let rows = rows(&table);
+        assert!(rows.iter().all(|row| row.len() == 12));
+        assert_eq!(rows[0][3..6], ["300ms", "400ms", "-"]);
+    }
+
+    #[test]
+    fn test_summary_shows_a_recorded_finalized_time_without_the_flag() {
+        let mut race = RaceBuilder::new(1).attempt("a").winner("a", 400).build();
+        race.winner.as_mut().unwrap().commitment_times = Some(CommitmentTimes {
+            finalized_ms: Some(13_000),
+            ..Default::default()
+        });
+
+        let table = generate_tx_summary_table(&race);
+
+        assert!(table.lines().next().unwrap().contains("Finalized"));
+        assert_eq!(rows(&table)[0][5], "13000ms");
+    }
+
+    #[test]
+    fn test_summary_lists_the_first_observed_transaction_after_the_winner() {
+        let mut race = RaceBuilder::new(1)
+            .attempt("a")
+            .attempt("b")
+            .attempt("c")
+            .winner("a", 400)
+            .non_winner("b", None)
+            .non_winner("c", None)
+            .build();
+        race.non_winners[0].commitment_times = Some(CommitmentTimes {
+            confirmed_ms: Some(450),
+            ..Default::default()
+        });
+        race.on_chain_order = Some(on_chain(&[
+            ("sig-1-b", 10, 0, true),
+            ("sig-1-a", 10, 1, false),
+        ]));
+        race.settle_on_chain_winner();
+        assert!(race.order_disagrees());
+
+        let table = generate_tx_summary_table(&race);
+
+        let rows = rows(&table);
+        let names: Vec<_> = rows.iter().map(|row| row[0]).collect();
+        assert_eq!(names, ["b", "a", "c"]);
+        assert!(
+            rows[0][2].starts_with("🏆 Confirmed (450ms"),
+            "{}",
+            rows[0][2]
+        );
+        assert!(rows[1][2].starts_with("⚠️ Confirmed (400ms"));
+        assert!(rows[1][2].ends_with("first observed but failed on chain"));
+        assert_eq!(rows[1].last(), Some(&"sig-1-a"));
+    }
+
+    #[test]
+    fn test_on_chain_table_is_in_block_order_then_not_included() {
+        let race = RaceBuilder::new(1)
+            .attempt("a")
+            .attempt("b")
+            .attempt("c")
+            .attempt("d")
+            .build();
+        let order = on_chain(&[
+            ("sig-1-a", 11, 0, false),
+            ("sig-1-c", 10, 5, true),
+            ("sig-1-d", 10, 2, false),
+        ]);
+
+        let table = generate_on_chain_table(&race, &order);
+
+        assert_eq!(
+            rows(&table),
+            [
+                ["d", "10", "2", "❌ Failed", "sig-1-d"],
+                ["c", "10", "5", "✅ Succeeded", "sig-1-c"],
+                ["a", "11", "0", "❌ Failed", "sig-1-a"],
+                ["b", "-", "-", "Not included", "sig-1-b"],
+            ]
+        );
+    }
+}
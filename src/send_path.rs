@@ -0,0 +1,822 @@
+use crate::bundle::{BundlePath, BundlePathConfig};
+use crate::endpoint::{build_header_map, redact_client_error, redact_url, Endpoint};
+use crate::timing::{SendTiming, TimedHttpClient};
+use crate::tpu::{TpuPath, TpuPathConfig};
+use crate::transactions::PreparedTransaction;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_client::client_error::reqwest::{
+    header::{HeaderMap, HeaderValue, CONTENT_TYPE},
+    Client as HttpClient,
+};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_sdk::{
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_transaction_status::UiTransactionEncoding;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How an endpoint submits its race transaction.
+///
+/// Whatever the path, the endpoint's `url` must be a JSON-RPC endpoint: it is
+/// used for lookups and to monitor the transaction after submission.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SendPathConfig {
+    /// JSON-RPC `sendTransaction` to the endpoint URL.
+    #[default]
+    Rpc,
+    /// JSON-RPC `sendTransaction` over a connection of its own, timing DNS,
+    /// TCP connect, TLS, request write and time to first byte of every send.
+    TimedRpc,
+    /// QUIC straight to the current and upcoming leaders' TPU, looked up through the endpoint URL.
+    Tpu(TpuPathConfig),
+    /// An HTTP POST of a templated body to a relay.
+    HttpRelay(HttpRelayConfig),
+    /// A block-engine `sendBundle` call with a tip transfer.
+    Bundle(BundlePathConfig),
+}
+
+impl SendPathConfig {
+    /// Short label used in reports and history.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SendPathConfig::Rpc => "rpc",
+            SendPathConfig::TimedRpc => "timed_rpc",
+            SendPathConfig::Tpu(_) => "tpu",
+            SendPathConfig::HttpRelay(_) => "http_relay",
+            SendPathConfig::Bundle(_) => "bundle",
+        }
+    }
+
+    /// Whether the path makes a `sendTransaction` call that `send_options` apply to.
+    pub fn uses_send_options(&self) -> bool {
+        matches!(self, SendPathConfig::Rpc | SendPathConfig::TimedRpc)
+    }
+
+    /// Checks the settings that can be checked without talking to the network.
+    pub fn validate(&self, endpoint_name: &str) -> Result<(), String> {
+        if let SendPathConfig::HttpRelay(relay) = self {
+            if relay.url.trim().is_empty() {
+                return Err(format!(
+                    "Endpoint '{}' has an empty relay URL.",
+                    endpoint_name
+                ));
+            }
+            if relay.body_template.trim().is_empty() {
+                return Err(format!(
+                    "Endpoint '{}' has an empty relay body_template.",
+                    endpoint_name
+                ));
+            }
+            relay.header_map(endpoint_name)?;
+        }
+        if let SendPathConfig::Bundle(bundle) = self {
+            if bundle.url.trim().is_empty() {
+                return Err(format!(
+                    "Endpoint '{}' has an empty block engine URL.",
+                    endpoint_name
+                ));
+            }
+            bundle.header_map(endpoint_name)?;
+        }
+        Ok(())
+    }
+
+    /// Creates the path for `endpoint`, connecting where the mechanism needs it.
+    ///
+    /// Connecting may block, so it is called on the endpoint's sender thread,
+    /// which has entered the shared multi-threaded Tokio runtime, or on a
+    /// `spawn_blocking` thread.
+    pub fn connect(&self, endpoint: &Endpoint) -> Result<Box<dyn SendPath>, String> {
+        Ok(match self {
+            SendPathConfig::Rpc => Box::new(RpcPath {
+                rpc_client: endpoint.nonblocking_rpc_client(CommitmentConfig::default()),
+                options: endpoint.send_options.effective(),
+            }),
+            SendPathConfig::TimedRpc => Box::new(TimedRpcPath {
+                client: TimedHttpClient::new(
+                    &endpoint.url,
+                    endpoint.headers.clone(),
+                    endpoint.timeout(),
+                )
+                .map_err(|e| format!("Endpoint '{}': {}", endpoint.name, e))?,
+                options: endpoint.send_options.effective(),
+            }),
+            SendPathConfig::Tpu(config) => Box::new(TpuPath::connect(endpoint, config)?),
+            SendPathConfig::HttpRelay(config) => Box::new(HttpRelayPath::new(endpoint, config)?),
+            SendPathConfig::Bundle(config) => Box::new(BundlePath::new(endpoint, config)?),
+        })
+    }
+}
+
+/// A race transaction in wire format, as handed to a send path.
+#[derive(Debug, Clone)]
+pub struct WireTransaction {
+    pub bytes: Vec<u8>,
+    /// The transaction's first signature.
+    pub signature: Signature,
+    /// The serialized tip transfer, for paths that bundle one.
+    pub tip: Option<Vec<u8>>,
+}
+
+impl WireTransaction {
+    pub fn serialize(prepared: &PreparedTransaction) -> Result<Self, String> {
+        let serialize = |transaction| {
+            bincode::serialize(transaction)
+                .map_err(|e| format!("Failed to serialize transaction: {}", e))
+        };
+        Ok(WireTransaction {
+            bytes: serialize(&prepared.transaction)?,
+            signature: prepared.signature,
+            tip: prepared
+                .tip_transaction
+                .as_ref()
+                .map(serialize)
+                .transpose()?,
+        })
+    }
+}
+
+/// What a path reports when it accepted a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    pub signature: Signature,
+    /// ID the block engine assigned, for bundle paths.
+    pub bundle_id: Option<String>,
+}
+
+impl From<Signature> for Submission {
+    fn from(signature: Signature) -> Self {
+        Submission {
+            signature,
+            bundle_id: None,
+        }
+    }
+}
+
+/// `sendTransaction` options of an RPC path. Unset fields fall back to the
+/// global `send_options`, then to the defaults of `RpcClient::send_transaction`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default, rename_all = "snake_case")]
+pub struct SendOptionsConfig {
+    pub skip_preflight: Option<bool>,
+    pub max_retries: Option<usize>,
+    pub preflight_commitment: Option<CommitmentLevel>,
+    /// `base58` or `base64`.
+    pub encoding: Option<UiTransactionEncoding>,
+    pub min_context_slot: Option<u64>,
+}
+
+impl SendOptionsConfig {
+    /// Returns `self` with unset fields taken from `fallback`.
+    pub fn or(self, fallback: SendOptionsConfig) -> Self {
+        SendOptionsConfig {
+            skip_preflight: self.skip_preflight.or(fallback.skip_preflight),
+            max_retries: self.max_retries.or(fallback.max_retries),
+            preflight_commitment: self.preflight_commitment.or(fallback.preflight_commitment),
+            encoding: self.encoding.or(fallback.encoding),
+            min_context_slot: self.min_context_slot.or(fallback.min_context_slot),
+        }
+    }
+
+    /// Fills in the defaults the RPC would otherwise apply. `max_retries` and
+    /// `min_context_slot` stay unset, leaving them to the provider.
+    pub fn effective(self) -> Self {
+        SendOptionsConfig {
+            skip_preflight: Some(self.skip_preflight.unwrap_or(false)),
+            preflight_commitment: Some(
+                self.preflight_commitment
+                    .unwrap_or(CommitmentConfig::default().commitment),
+            ),
+            encoding: Some(self.encoding.unwrap_or(UiTransactionEncoding::Base64)),
+            ..self
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self.encoding {
+            None | Some(UiTransactionEncoding::Base58) | Some(UiTransactionEncoding::Base64) => {
+                Ok(())
+            }
+            Some(other) => Err(format!(
+                "send_options.encoding must be base58 or base64, got {}.",
+                other
+            )),
+        }
+    }
+
+    /// `sendTransaction` params for a serialized transaction.
+    fn send_transaction_params(&self, wire_transaction: &[u8]) -> Value {
+        let encoded = match self.encoding {
+            Some(UiTransactionEncoding::Base58) => bs58::encode(wire_transaction).into_string(),
+            _ => BASE64_STANDARD.encode(wire_transaction),
+        };
+        json!([encoded, self.rpc_config()])
+    }
+
+    fn rpc_config(&self) -> RpcSendTransactionConfig {
+        RpcSendTransactionConfig {
+            skip_preflight: self.skip_preflight.unwrap_or(false),
+            preflight_commitment: self.preflight_commitment,
+            encoding: self.encoding,
+            max_retries: self.max_retries,
+            min_context_slot: self.min_context_slot,
+        }
+    }
+}
+
+/// Timing and outcome of one submission.
+#[derive(Debug, Clone)]
+pub struct SendOutcome {
+    /// The signature the path reported, or why the submission failed.
+    pub result: Result<Signature, String>,
+    pub bundle_id: Option<String>,
+    /// Dispatch instant: when the request was written, for paths that report
+    /// it, otherwise when the submission started.
+    pub start_instant: Instant,
+    /// Whether `start_instant` is the request write rather than the start of
+    /// the submission.
+    pub dispatch_at_write: bool,
+    /// When the submission started, for every path.
+    pub submitted_at: Instant,
+    /// Wall-clock time of `start_instant`, for persisted history.
+    pub start_time: SystemTime,
+    /// The whole submission, from handing over the transaction to the response.
+    pub duration: Duration,
+    /// Phases of the submission, for paths that time them.
+    pub timing: Option<SendTiming>,
+    /// Setup of the connection the submission went over, for paths that time
+    /// it; also set when the warm-up opened that connection.
+    pub connection_timing: Option<SendTiming>,
+}
+
+impl SendOutcome {
+    /// An outcome for a transaction that could not be handed to its path at all.
+    pub fn failed(error: String) -> Self {
+        SendOutcome {
+            result: Err(error),
+            bundle_id: None,
+            start_instant: Instant::now(),
+            start_time: SystemTime::now(),
+            dispatch_at_write: false,
+            submitted_at: Instant::now(),
+            duration: Duration::ZERO,
+            timing: None,
+            connection_timing: None,
+        }
+    }
+}
+
+/// Drives `future` to completion on the shared runtime from synchronous code.
+///
+/// Works on sender threads that entered the runtime, on `spawn_blocking`
+/// threads and on runtime workers, so send paths need no runtime of their own.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(future))
+}
+
+/// A submission in flight; resolves once the path has accepted or rejected the transaction.
+pub type Submit<'a> = Pin<Box<dyn Future<Output = Result<Submission, String>> + Send + 'a>>;
+
+/// A mechanism for submitting a signed transaction.
+pub trait SendPath: Send + Sync {
+    /// Submits a serialized transaction.
+    fn submit<'a>(&'a self, transaction: &'a WireTransaction) -> Submit<'a>;
+
+    /// Opens the connection the next submission will use, so that DNS, TCP and
+    /// TLS setup are not part of the race. `None` if the path has nothing to warm.
+    fn warm_up(&self) -> Option<Result<(), String>> {
+        None
+    }
+
+    /// Phase timing of the latest submission, for paths that record it.
+    fn last_timing(&self) -> Option<SendTiming> {
+        None
+    }
+
+    /// Setup of the connection the latest submission used, for paths that record it.
+    fn connection_timing(&self) -> Option<SendTiming> {
+        None
+    }
+
+    /// When the latest submission's request was written to the socket, for
+    /// paths that write it themselves.
+    fn last_written_at(&self) -> Option<Instant> {
+        None
+    }
+
+    /// Submits the transaction and measures how long the submission took.
+    fn send_async<'a>(
+        &'a self,
+        transaction: &'a WireTransaction,
+    ) -> Pin<Box<dyn Future<Output = SendOutcome> + Send + 'a>> {
+        Box::pin(async move {
+            let start_instant = Instant::now();
+            let start_time = SystemTime::now();
+            let submission = self.submit(transaction).await;
+            let duration = start_instant.elapsed();
+            let (result, bundle_id) = match submission {
+                Ok(submission) => (Ok(submission.signature), submission.bundle_id),
+                Err(e) => (Err(e), None),
+            };
+            // Dispatch is when the request hit the wire, where the path knows it.
+            let written_at = self.last_written_at();
+            let submitted_at = start_instant;
+            let (start_instant, start_time) = match written_at {
+                Some(written_at) => (
+                    written_at,
+                    start_time + written_at.saturating_duration_since(start_instant),
+                ),
+                None => (start_instant, start_time),
+            };
+            SendOutcome {
+                result,
+                bundle_id,
+                start_instant,
+                start_time,
+                dispatch_at_write: written_at.is_some(),
+                submitted_at,
+                duration,
+                timing: self.last_timing(),
+                connection_timing: self.connection_timing(),
+            }
+        })
+    }
+
+    /// [`SendPath::send_async`] for a dedicated sender thread, which blocks
+    /// until the submission is done.
+    fn send(&self, transaction: &WireTransaction) -> SendOutcome {
+        block_on(self.send_async(transaction))
+    }
+}
+
+/// JSON-RPC `sendTransaction` with the endpoint's effective send options.
+pub struct RpcPath {
+    rpc_client: RpcClient,
+    options: SendOptionsConfig,
+}
+
+impl SendPath for RpcPath {
+    /// Goes through `send_transaction_with_config`, which also decodes preflight
+    /// failures into their simulation logs.
+    fn submit<'a>(&'a self, transaction: &'a WireTransaction) -> Submit<'a> {
+        Box::pin(async move {
+            let decoded: VersionedTransaction = bincode::deserialize(&transaction.bytes)
+                .map_err(|e| format!("Failed to deserialize transaction: {}", e))?;
+            self.rpc_client
+                .send_transaction_with_config(&decoded, self.options.rpc_config())
+                .await
+                .map(Submission::from)
+                .map_err(redact_client_error)
+        })
+    }
+
+    fn warm_up(&self) -> Option<Result<(), String>> {
+        Some(
+            block_on(self.rpc_client.get_slot())
+                .map(|_| ())
+                .map_err(redact_client_error),
+        )
+    }
+}
+
+/// Checks the signature an RPC node returned for `transaction`.
+fn check_returned_signature(
+    returned: &str,
+    transaction: &WireTransaction,
+) -> Result<Submission, String> {
+    let returned: Signature = returned
+        .parse()
+        .map_err(|e| format!("RPC returned an invalid signature: {}", e))?;
+    if returned != transaction.signature {
+        return Err(format!(
+            "RPC node returned mismatched signature {}, expected {}",
+            returned, transaction.signature
+        ));
+    }
+    Ok(returned.into())
+}
+
+/// Same call as [`RpcPath`], made through a [`TimedHttpClient`].
+pub struct TimedRpcPath {
+    client: TimedHttpClient,
+    options: SendOptionsConfig,
+}
+
+impl SendPath for TimedRpcPath {
+    fn submit<'a>(&'a self, transaction: &'a WireTransaction) -> Submit<'a> {
+        Box::pin(async move {
+            let body = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "sendTransaction",
+                "params": self.options.send_transaction_params(&transaction.bytes),
+            });
+            let response = self.client.post(body.to_string().as_bytes()).await?;
+            let parsed: Value = serde_json::from_slice(&response.body).map_err(|e| {
+                format!(
+                    "RPC returned HTTP {} with an invalid body: {}",
+                    response.status, e
+                )
+            })?;
+            if let Some(error) = parsed.get("error") {
+                return Err(format!(
+                    "RPC response error {}: {}",
+                    error["code"],
+                    error["message"].as_str().unwrap_or_default()
+                ));
+            }
+            let returned = parsed["result"]
+                .as_str()
+                .ok_or_else(|| format!("RPC returned HTTP {} without a result", response.status))?;
+            check_returned_signature(returned, transaction)
+        })
+    }
+
+    fn warm_up(&self) -> Option<Result<(), String>> {
+        Some(block_on(self.client.warm_up()))
+    }
+
+    fn last_timing(&self) -> Option<SendTiming> {
+        self.client.last_timing()
+    }
+
+    fn connection_timing(&self) -> Option<SendTiming> {
+        self.client.connection_timing()
+    }
+
+    fn last_written_at(&self) -> Option<Instant> {
+        self.client.last_written_at()
+    }
+}
+
+/// Settings of a custom HTTP relay.
+///
+/// `body_template` may contain `{{transaction_base64}}`, `{{transaction_base58}}`
+/// and `{{signature}}`, which are replaced before every submission.
+#[derive(Deserialize, Clone, PartialEq, Eq)]
+pub struct HttpRelayConfig {
+    pub url: String,
+    /// Extra headers, e.g. an API key. `Content-Type` defaults to `application/json`.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body_template: String,
+}
+
+impl HttpRelayConfig {
+    fn header_map(&self, endpoint_name: &str) -> Result<HeaderMap, String> {
+        let mut base = HeaderMap::new();
+        base.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        build_header_map(endpoint_name, base, &self.headers)
+    }
+}
+
+impl fmt::Debug for HttpRelayConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpRelayConfig")
+            .field("url", &redact_url(&self.url))
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .field("body_template", &self.body_template)
+            .finish()
+    }
+}
+
+/// Opens a connection to `url` with a `HEAD` request. Any HTTP response will
+/// do, since only the connection is of interest.
+pub fn warm_up_http(client: &HttpClient, url: &str) -> Result<(), String> {
+    let request = client.head(url);
+    block_on(async {
+        request
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|e| e.without_url().to_string())
+    })
+}
+
+/// Fills the relay body template for one transaction.
+pub fn render_body_template(
+    template: &str,
+    wire_transaction: &[u8],
+    signature: &Signature,
+) -> String {
+    template
+        .replace(
+            "{{transaction_base64}}",
+            &BASE64_STANDARD.encode(wire_transaction),
+        )
+        .replace(
+            "{{transaction_base58}}",
+            &bs58::encode(wire_transaction).into_string(),
+        )
+        .replace("{{signature}}", &signature.to_string())
+}
+
+/// POSTs the rendered template to the relay; any 2xx response counts as accepted.
+pub struct HttpRelayPath {
+    client: HttpClient,
+    url: String,
+    body_template: String,
+}
+
+impl HttpRelayPath {
+    pub fn new(endpoint: &Endpoint, config: &HttpRelayConfig) -> Result<Self, String> {
+        let client = HttpClient::builder()
+            .default_headers(config.header_map(&endpoint.name)?)
+            .timeout(endpoint.timeout())
+            .build()
+            .map_err(|e| format!("Endpoint '{}': {}", endpoint.name, e))?;
+        Ok(HttpRelayPath {
+            client,
+            url: config.url.clone(),
+            body_template: config.body_template.clone(),
+        })
+    }
+}
+
+impl SendPath for HttpRelayPath {
+    fn submit<'a>(&'a self, transaction: &'a WireTransaction) -> Submit<'a> {
+        let body = render_body_template(
+            &self.body_template,
+            &transaction.bytes,
+            &transaction.signature,
+        );
+        let request = self.client.post(&self.url).body(body);
+        Box::pin(async move {
+            // Strip the URL from errors, as it may carry an API key.
+            let response = request
+                .send()
+                .await
+                .map_err(|e| e.without_url().to_string())?;
+            let status = response.status();
+            if status.is_success() {
+                Ok(transaction.signature.into())
+            } else {
+                let text = response.text().await.unwrap_or_default();
+                Err(format!(
+                    "Relay returned HTTP {}: {}",
+                    status,
+                    text.chars().take(200).collect::<String>()
+                ))
+            }
+        })
+    }
+
+    fn warm_up(&self) -> Option<Result<(), String>> {
+        Some(warm_up_http(&self.client, &self.url))
+    }
+}
+
+/// A minimal HTTP server for exercising paths against canned responses.
+#[cfg(test)]
+pub(crate) mod mock_http {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    /// Answers one request per entry of `responses`, given as status line and
+    /// body, on a local port. Request bodies are handed back through the channel.
+    pub fn serve(responses: Vec<(&'static str, String)>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let (body_tx, body_rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for (status_line, response_body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let _ = body_tx.send(String::from_utf8(body).unwrap());
+                write!(
+                    reader.into_inner(),
+                    "{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status_line,
+                    response_body.len(),
+                    response_body
+                )
+                .unwrap();
+            }
+        });
+        (url, body_rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relay(url: String) -> HttpRelayPath {
+        let endpoint = Endpoint::from_url("relay".to_string(), "http://localhost:8899".to_string());
+        HttpRelayPath::new(
+            &endpoint,
+            &HttpRelayConfig {
+                url,
+                headers: BTreeMap::new(),
+                body_template: r#"{"tx":"{{transaction_base64}}","sig":"{{signature}}"}"#
+                    .to_string(),
+            },
+        )
+        .unwrap()
+    }
+
+    fn wire_transaction() -> WireTransaction {
+        WireTransaction {
+            bytes: vec![1, 2, 3],
+            signature: Signature::default(),
+            tip: None,
+        }
+    }
+
+    #[test]
+    fn test_send_options_fall_back_and_fill_defaults() {
+        let endpoint = SendOptionsConfig {
+            skip_preflight: Some(true),
+            ..SendOptionsConfig::default()
+        };
+        let global = SendOptionsConfig {
+            skip_preflight: Some(false),
+            max_retries: Some(0),
+            ..SendOptionsConfig::default()
+        };
+
+        let effective = endpoint.or(global).effective();
+
+        assert_eq!(
+            effective,
+            SendOptionsConfig {
+                skip_preflight: Some(true),
+                max_retries: Some(0),
+                preflight_commitment: Some(CommitmentLevel::Finalized),
+                encoding: Some(UiTransactionEncoding::Base64),
+                min_context_slot: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_render_body_template() {
+        let signature = Signature::default();
+        let body = render_body_template(
+            "{{transaction_base64}} {{transaction_base58}} {{signature}}",
+            &[1, 2, 3],
+            &signature,
+        );
+        assert_eq!(body, format!("AQID Ldp {}", signature));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_http_relay_posts_rendered_body() {
+        let (url, body_rx) = mock_http::serve(vec![("HTTP/1.1 200 OK", "ok".to_string())]);
+        let signature = Signature::default();
+
+        let outcome = relay(url).send(&wire_transaction());
+
+        assert_eq!(outcome.result, Ok(signature));
+        assert_eq!(
+            body_rx.recv().unwrap(),
+            format!(r#"{{"tx":"AQID","sig":"{}"}}"#, signature)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rpc_path_sends_with_effective_options() {
+        let (url, body_rx) = mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            format!(
+                r#"{{"jsonrpc":"2.0","id":1,"result":"{}"}}"#,
+                Signature::default()
+            ),
+        )]);
+        let mut endpoint = Endpoint::from_url("rpc".to_string(), url);
+        endpoint.send_options = SendOptionsConfig {
+            skip_preflight: Some(true),
+            max_retries: Some(0),
+            ..SendOptionsConfig::default()
+        };
+        let transaction = solana_sdk::transaction::Transaction::new_with_payer(
+            &[],
+            Some(&solana_sdk::pubkey::Pubkey::new_unique()),
+        );
+        let wire = WireTransaction {
+            bytes: bincode::serialize(&transaction).unwrap(),
+            ..wire_transaction()
+        };
+
+        let outcome = SendPathConfig::Rpc.connect(&endpoint).unwrap().send(&wire);
+
+        assert_eq!(outcome.result, Ok(Signature::default()));
+        let request: Value = serde_json::from_str(&body_rx.recv().unwrap()).unwrap();
+        assert_eq!(request["method"], "sendTransaction");
+        assert_eq!(request["params"][1]["skipPreflight"], true);
+        assert_eq!(request["params"][1]["maxRetries"], 0);
+        assert_eq!(request["params"][0], BASE64_STANDARD.encode(&wire.bytes));
+        assert_eq!(request["params"][1]["encoding"], "base64");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rpc_path_reports_preflight_failure_logs() {
+        let (url, _body_rx) = mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32002,"message":"Transaction simulation failed: Attempt to debit an account but found no record of a prior credit.","data":{"err":"AccountNotFound","logs":["Program log: insufficient funds"]}}}"#
+                .to_string(),
+        )]);
+        let endpoint = Endpoint::from_url("rpc".to_string(), url);
+        let transaction = solana_sdk::transaction::Transaction::new_with_payer(
+            &[],
+            Some(&solana_sdk::pubkey::Pubkey::new_unique()),
+        );
+        let wire = WireTransaction {
+            bytes: bincode::serialize(&transaction).unwrap(),
+            ..wire_transaction()
+        };
+
+        let outcome = SendPathConfig::Rpc.connect(&endpoint).unwrap().send(&wire);
+
+        let error = outcome.result.unwrap_err();
+        assert!(error.contains("Transaction simulation failed"));
+        assert!(error.contains("Program log: insufficient funds"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rpc_path_errors_do_not_leak_api_key() {
+        let endpoint = Endpoint::from_url(
+            "rpc".to_string(),
+            "http://127.0.0.1:1/?api-key=SECRET".to_string(),
+        );
+        let transaction = solana_sdk::transaction::Transaction::new_with_payer(
+            &[],
+            Some(&solana_sdk::pubkey::Pubkey::new_unique()),
+        );
+        let wire = WireTransaction {
+            bytes: bincode::serialize(&transaction).unwrap(),
+            ..wire_transaction()
+        };
+        let path = SendPathConfig::Rpc.connect(&endpoint).unwrap();
+
+        let warm_up_error = path.warm_up().unwrap().unwrap_err();
+        let outcome = path.send(&wire);
+        let error = outcome.result.unwrap_err();
+        assert!(!warm_up_error.contains("SECRET"), "{}", warm_up_error);
+        assert!(!error.contains("SECRET"), "{}", error);
+
+        let attempt = crate::transactions::SendAttempt {
+            send_result: Err(error),
+            warm_up_error: Some(warm_up_error),
+            ..crate::transactions::SendAttempt::accepted(endpoint)
+        };
+        let outcome = crate::race::RaceOutcome {
+            started_at: std::time::SystemTime::now(),
+            send_attempts: vec![attempt],
+            winner: None,
+            non_winners: Vec::new(),
+            fee_selection: None,
+            dispatch_skew: None,
+            unfair: false,
+            mixed_dispatch_reference: false,
+            dispatch_order: crate::dispatch_order::DispatchOrderConfig::Fixed.order(1, 0),
+            on_chain_order: None,
+            slot_timeline: Default::default(),
+            finalized_tracked: false,
+        };
+        let record = crate::history::RaceRecord::from_outcome(&outcome, "fp".to_string());
+        let stored = serde_json::to_string(&record).unwrap();
+        assert!(record.send_attempts[0].error.is_some());
+        assert!(!stored.contains("SECRET"), "{}", stored);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_http_relay_reports_error_status() {
+        let (url, _body_rx) = mock_http::serve(vec![(
+            "HTTP/1.1 503 Service Unavailable",
+            "busy".to_string(),
+        )]);
+
+        let outcome = relay(url).send(&wire_transaction());
+
+        assert!(outcome.result.unwrap_err().contains("503"));
+    }
+}
@@ -0,0 +1,263 @@
+use crate::endpoint::{redact_client_error, Endpoint};
+use crate::history::unix_millis;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Instant, SystemTime},
+};
+use tokio::{sync::oneshot, task::JoinHandle};
+
+/// A slot and when this machine first learned about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotSample {
+    pub slot: u64,
+    pub seen_at: Instant,
+}
+
+/// When the slot clock's endpoint reported a slot, in Unix milliseconds.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotArrival {
+    pub slot: u64,
+    /// First slot notification or `getSlot` sample for the slot.
+    pub seen_at_ms: Option<u64>,
+    /// Root notification for the slot.
+    pub rooted_at_ms: Option<u64>,
+}
+
+/// Slot arrivals over a race window, in slot order.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SlotTimeline {
+    /// Endpoint the slots were read from.
+    pub source: String,
+    pub arrivals: Vec<SlotArrival>,
+}
+
+impl SlotTimeline {
+    /// When the endpoint moved past `slot`, i.e. first reported a later one.
+    /// A transaction landed in `slot` was in a complete block by then.
+    pub fn slot_ended_at_ms(&self, slot: u64) -> Option<u64> {
+        self.arrivals
+            .iter()
+            .filter(|arrival| arrival.slot > slot)
+            .filter_map(|arrival| arrival.seen_at_ms)
+            .min()
+    }
+
+    pub fn rooted_at_ms(&self, slot: u64) -> Option<u64> {
+        self.arrivals
+            .iter()
+            .find(|arrival| arrival.slot == slot)
+            .and_then(|arrival| arrival.rooted_at_ms)
+    }
+}
+
+#[derive(Default)]
+struct Samples {
+    slots: Vec<SlotSample>,
+    roots: Vec<SlotSample>,
+}
+
+/// Maps local instants to the cluster's slot, from `slotSubscribe` and
+/// `rootSubscribe` notifications and explicit `getSlot` samples.
+#[derive(Default)]
+pub struct SlotClock {
+    samples: Arc<Mutex<Samples>>,
+    subscription: Option<JoinHandle<()>>,
+}
+
+impl SlotClock {
+    /// Follows `endpoint`'s slot and root notifications in the background
+    /// until [`SlotClock::stop`]. Returns once both subscriptions are in place.
+    pub async fn subscribe(&mut self, endpoint: &Endpoint) -> Result<(), String> {
+        let websocket_url = endpoint.websocket_url();
+        let samples = self.samples.clone();
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            let pubsub = match PubsubClient::new(&websocket_url).await {
+                Ok(pubsub) => pubsub,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e.to_string()));
+                    return;
+                }
+            };
+            let subscriptions = tokio::try_join!(pubsub.slot_subscribe(), pubsub.root_subscribe());
+            let ((mut slots, _unsubscribe_slots), (mut roots, _unsubscribe_roots)) =
+                match subscriptions {
+                    Ok(subscriptions) => subscriptions,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e.to_string()));
+                        return;
+                    }
+                };
+            let _ = ready_tx.send(Ok(()));
+            loop {
+                tokio::select! {
+                    Some(update) = slots.next() => {
+                        record(&mut lock(&samples).slots, update.slot, Instant::now());
+                    }
+                    Some(root) = roots.next() => {
+                        record(&mut lock(&samples).roots, root, Instant::now());
+                    }
+                    else => break,
+                }
+            }
+        });
+        match ready_rx.await {
+            Ok(Ok(())) => {
+                self.subscription = Some(task);
+                Ok(())
+            }
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err("slot subscription task ended".to_string()),
+        }
+    }
+
+    /// Records `endpoint`'s processed slot from `getSlot`, as seen when the
+    /// response arrived.
+    pub async fn sample(&self, endpoint: &Endpoint) -> Result<u64, String> {
+        let slot = endpoint
+            .nonblocking_rpc_client(CommitmentConfig::processed())
+            .get_slot()
+            .await
+            .map_err(redact_client_error)?;
+        self.record(slot, Instant::now());
+        Ok(slot)
+    }
+
+    /// Records a slot learned some other way, e.g. from `getSlot`.
+    pub fn record(&self, slot: u64, seen_at: Instant) {
+        record(&mut lock(&self.samples).slots, slot, seen_at);
+    }
+
+    /// The latest slot known at `instant`, or `None` if no slot was known yet.
+    pub fn slot_at(&self, instant: Instant) -> Option<u64> {
+        lock(&self.samples)
+            .slots
+            .iter()
+            .filter(|sample| sample.seen_at <= instant)
+            .map(|sample| sample.slot)
+            .max()
+    }
+
+    /// Everything recorded so far, with instants converted to wall-clock time.
+    pub fn timeline(&self, source: &str) -> SlotTimeline {
+        let (now, now_time) = (Instant::now(), SystemTime::now());
+        let to_millis = |instant: Instant| unix_millis(now_time - now.duration_since(instant));
+        let samples = lock(&self.samples);
+        let mut arrivals: Vec<SlotArrival> = samples
+            .slots
+            .iter()
+            .map(|sample| SlotArrival {
+                slot: sample.slot,
+                seen_at_ms: Some(to_millis(sample.seen_at)),
+                rooted_at_ms: None,
+            })
+            .collect();
+        for root in &samples.roots {
+            match arrivals.iter_mut().find(|a| a.slot == root.slot) {
+                Some(arrival) => arrival.rooted_at_ms = Some(to_millis(root.seen_at)),
+                None => arrivals.push(SlotArrival {
+                    slot: root.slot,
+                    seen_at_ms: None,
+                    rooted_at_ms: Some(to_millis(root.seen_at)),
+                }),
+            }
+        }
+        arrivals.sort_by_key(|arrival| arrival.slot);
+        SlotTimeline {
+            source: source.to_string(),
+            arrivals,
+        }
+    }
+
+    /// Ends the subscriptions, if any.
+    pub fn stop(&mut self) {
+        if let Some(task) = self.subscription.take() {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for SlotClock {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn lock(samples: &Mutex<Samples>) -> MutexGuard<'_, Samples> {
+    samples.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Keeps only the first sighting of every slot.
+fn record(samples: &mut Vec<SlotSample>, slot: u64, seen_at: Instant) {
+    if !samples.iter().any(|sample| sample.slot == slot) {
+        samples.push(SlotSample { slot, seen_at });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_slot_at_is_latest_slot_seen_before_the_instant() {
+        let clock = SlotClock::default();
+        let start = Instant::now();
+        clock.record(100, start + Duration::from_millis(10));
+        clock.record(101, start + Duration::from_millis(410));
+        clock.record(101, start + Duration::from_millis(500));
+
+        assert_eq!(clock.slot_at(start), None);
+        assert_eq!(clock.slot_at(start + Duration::from_millis(200)), Some(100));
+        assert_eq!(clock.slot_at(start + Duration::from_millis(410)), Some(101));
+    }
+
+    #[tokio::test]
+    async fn test_slot_at_falls_back_to_get_slot_samples() {
+        let (url, requests) = crate::send_path::mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            r#"{"jsonrpc":"2.0","id":1,"result":42}"#.to_string(),
+        )]);
+        let endpoint = crate::test_support::endpoint_without_websocket("observer", url);
+        let mut clock = SlotClock::default();
+        assert!(clock.subscribe(&endpoint).await.is_err());
+
+        let before = Instant::now();
+        assert_eq!(clock.sample(&endpoint).await, Ok(42));
+
+        assert_eq!(clock.slot_at(before), None);
+        assert_eq!(clock.slot_at(Instant::now()), Some(42));
+        let request: serde_json::Value = serde_json::from_str(&requests.recv().unwrap()).unwrap();
+        assert_eq!(request["method"], "getSlot");
+        assert_eq!(request["params"][0]["commitment"], "processed");
+        let timeline = clock.timeline("observer");
+        assert_eq!(timeline.arrivals.len(), 1);
+        assert_eq!(timeline.arrivals[0].slot, 42);
+    }
+
+    #[test]
+    fn test_slot_ends_when_a_later_slot_is_seen() {
+        let arrival = |slot, seen_at_ms| SlotArrival {
+            slot,
+            seen_at_ms,
+            rooted_at_ms: None,
+        };
+        let timeline = SlotTimeline {
+            source: "observer".to_string(),
+            arrivals: vec![
+                arrival(100, Some(1_000)),
+                arrival(101, None),
+                arrival(103, Some(1_800)),
+                arrival(102, Some(1_400)),
+            ],
+        };
+
+        assert_eq!(timeline.slot_ended_at_ms(100), Some(1_400));
+        assert_eq!(timeline.slot_ended_at_ms(102), Some(1_800));
+        assert_eq!(timeline.slot_ended_at_ms(103), None);
+    }
+}
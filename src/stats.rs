@@ -0,0 +1,268 @@
+use crate::history::RaceRecord;
+
+/// Summary of a set of millisecond samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spread {
+    pub min: u64,
+    pub median: u64,
+    pub mean: f64,
+    pub max: u64,
+}
+
+impl Spread {
+    /// Returns `None` when there are no samples.
+    pub fn from_samples(samples: &[u64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let sum: u64 = sorted.iter().sum();
+        Some(Spread {
+            min: sorted[0],
+            median: percentile(&sorted, 50.0),
+            mean: sum as f64 / sorted.len() as f64,
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Nearest-rank percentile of an already sorted, non-empty slice.
+pub fn percentile(sorted: &[u64], p: f64) -> u64 {
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Nearest-rank percentiles of unsorted samples, or `None` when there are none.
+pub fn percentiles(samples: &[u64], ps: &[f64]) -> Option<Vec<u64>> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    Some(ps.iter().map(|p| percentile(&sorted, *p)).collect())
+}
+
+/// Aggregated results of one RPC endpoint across several races.
+#[derive(Debug, Clone, Default)]
+pub struct EndpointStats {
+    pub endpoint_name: String,
+    pub races: usize,
+    pub wins: usize,
+    /// Races where the transaction was sent successfully but another one won.
+    pub losses: usize,
+    pub send_failures: usize,
+    /// `time_to_confirm_ms` of every race this endpoint won.
+    pub confirm_times_ms: Vec<u64>,
+    /// Slots between dispatch and landing of every race this endpoint won.
+    pub slots_to_land: Vec<u64>,
+    pub send_durations_ms: Vec<u64>,
+}
+
+impl EndpointStats {
+    pub fn win_rate(&self) -> f64 {
+        if self.races == 0 {
+            0.0
+        } else {
+            self.wins as f64 / self.races as f64
+        }
+    }
+}
+
+/// Aggregates per-endpoint statistics over all races, in order of first appearance.
+pub fn aggregate_endpoint_stats(races: &[RaceRecord]) -> Vec<EndpointStats> {
+    let mut stats: Vec<EndpointStats> = Vec::new();
+
+    for race in races {
+        for attempt in &race.send_attempts {
+            let index = match stats
+                .iter()
+                .position(|s| s.endpoint_name == attempt.endpoint_name)
+            {
+                Some(index) => index,
+                None => {
+                    stats.push(EndpointStats {
+                        endpoint_name: attempt.endpoint_name.clone(),
+                        ..Default::default()
+                    });
+                    stats.len() - 1
+                }
+            };
+            let entry = &mut stats[index];
+
+            entry.races += 1;
+            entry.send_durations_ms.push(attempt.send_duration_ms);
+
+            if race.winning_signature() == Some(attempt.signature.as_str()) {
+                entry.wins += 1;
+                entry.slots_to_land.extend(race.slots_to_land(attempt));
+                // Races stored before the on-chain winner was settled may
+                // hold an observed winner that lost on chain.
+                if let Some(ms) = race
+                    .winner
+                    .as_ref()
+                    .filter(|w| w.signature == attempt.signature)
+                    .and_then(|w| w.time_to_confirm_ms)
+                {
+                    entry.confirm_times_ms.push(ms);
+                }
+            } else if attempt.error.is_some() {
+                entry.send_failures += 1;
+            } else {
+                entry.losses += 1;
+            }
+        }
+    }
+
+    stats
+}
+
+/// Wins per dispatch position across races, to spot a bias towards going first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PositionStats {
+    /// 0 being dispatched first.
+    pub position: u32,
+    pub races: usize,
+    pub wins: usize,
+}
+
+/// Aggregates wins by dispatch position, ordered by position. Attempts stored
+/// without a position are skipped.
+pub fn aggregate_position_stats(races: &[RaceRecord]) -> Vec<PositionStats> {
+    let mut stats: Vec<PositionStats> = Vec::new();
+    for race in races {
+        for attempt in &race.send_attempts {
+            let Some(position) = attempt.position else {
+                continue;
+            };
+            let index = match stats.iter().position(|s| s.position == position) {
+                Some(index) => index,
+                None => {
+                    stats.push(PositionStats {
+                        position,
+                        ..Default::default()
+                    });
+                    stats.len() - 1
+                }
+            };
+            stats[index].races += 1;
+            if race.winning_signature() == Some(attempt.signature.as_str()) {
+                stats[index].wins += 1;
+            }
+        }
+    }
+    stats.sort_by_key(|s| s.position);
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{RaceBuilder, SendAttemptRecord};
+
+    fn sent_in(ms: u64) -> impl FnOnce(&mut SendAttemptRecord) {
+        move |a| a.send_duration_ms = ms
+    }
+
+    fn failed_in(ms: u64) -> impl FnOnce(&mut SendAttemptRecord) {
+        move |a| {
+            a.send_duration_ms = ms;
+            a.error = Some("send failed".to_string());
+        }
+    }
+
+    #[test]
+    fn test_aggregate_counts_wins_losses_and_send_failures() {
+        let races = vec![
+            RaceBuilder::new(0)
+                .attempt_with("a", |a| {
+                    a.send_duration_ms = 10;
+                    a.dispatch_slot = Some(0);
+                })
+                .attempt_with("b", sent_in(20))
+                .winner("a", 100)
+                .build(),
+            RaceBuilder::new(1)
+                .attempt_with("a", failed_in(30))
+                .attempt_with("b", sent_in(40))
+                .winner("b", 200)
+                .build(),
+            RaceBuilder::new(2)
+                .attempt_with("a", sent_in(50))
+                .attempt_with("b", sent_in(60))
+                .build(),
+        ];
+
+        let stats = aggregate_endpoint_stats(&races);
+
+        assert_eq!(stats.len(), 2);
+        let a = &stats[0];
+        assert_eq!(a.endpoint_name, "a");
+        assert_eq!((a.races, a.wins, a.losses, a.send_failures), (3, 1, 1, 1));
+        assert_eq!(a.confirm_times_ms, vec![100]);
+        assert_eq!(a.slots_to_land, vec![1]);
+        assert_eq!(a.send_durations_ms, vec![10, 30, 50]);
+        let b = &stats[1];
+        assert_eq!((b.races, b.wins, b.losses, b.send_failures), (3, 1, 2, 0));
+        assert_eq!(b.confirm_times_ms, vec![200]);
+        assert!(b.slots_to_land.is_empty());
+    }
+
+    #[test]
+    fn test_aggregate_position_stats() {
+        let at = |position: u32| move |a: &mut SendAttemptRecord| a.position = Some(position);
+        let races = vec![
+            RaceBuilder::new(0)
+                .attempt_with("a", at(0))
+                .attempt_with("b", at(1))
+                .winner("a", 100)
+                .build(),
+            RaceBuilder::new(1)
+                .attempt_with("b", at(0))
+                .attempt_with("a", at(1))
+                .winner("b", 100)
+                .build(),
+            RaceBuilder::new(2).attempt("a").winner("a", 100).build(),
+        ];
+
+        let stats = aggregate_position_stats(&races);
+
+        assert_eq!(
+            stats,
+            vec![
+                PositionStats {
+                    position: 0,
+                    races: 2,
+                    wins: 2
+                },
+                PositionStats {
+                    position: 1,
+                    races: 2,
+                    wins: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spread_from_samples() {
+        assert_eq!(Spread::from_samples(&[]), None);
+        let spread = Spread::from_samples(&[40, 10, 30, 20]).unwrap();
+        assert_eq!(spread.min, 10);
+        assert_eq!(spread.median, 20);
+        assert_eq!(spread.mean, 25.0);
+        assert_eq!(spread.max, 40);
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let sorted: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&sorted, 50.0), 50);
+        assert_eq!(percentile(&sorted, 90.0), 90);
+        assert_eq!(percentile(&sorted, 99.0), 99);
+        assert_eq!(percentile(&sorted, 0.0), 1);
+        assert_eq!(percentile(&[7], 99.0), 7);
+        assert_eq!(percentiles(&[3, 1, 2], &[50.0, 100.0]), Some(vec![2, 3]));
+        assert_eq!(percentiles(&[], &[50.0]), None);
+    }
+}
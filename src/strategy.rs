@@ -0,0 +1,564 @@
+use crate::accounts::AccountInfo;
+use crate::fees::ComputeBudgetConfig;
+use crate::nonce::{fetch_nonce, nonce_authority, DurableNonceConfig, NonceInfo};
+use crate::transactions::TransactionFormat;
+use serde::Deserialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    hash::Hash, instruction::Instruction, pubkey, pubkey::Pubkey, signature::Keypair,
+    system_instruction, transaction::VersionedTransaction,
+};
+use std::error::Error;
+
+/// Minimum balance to leave in the sender's account after a transaction, in lamports,
+/// on top of the largest fee any of the conflicting transactions can be charged.
+pub const MIN_SENDER_RESERVE_LAMPORTS: u64 = 5_000;
+
+/// The SPL Memo program, used to make otherwise identical transfers distinct.
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// One of the mutually exclusive transactions, before it is assigned to an endpoint.
+#[derive(Debug)]
+pub struct ConflictingTransaction {
+    pub transaction: VersionedTransaction,
+    pub amount_lamports: u64,
+    /// Signature plus prioritization fee the transaction pays if it lands.
+    pub fee_lamports: u64,
+}
+
+/// Produces transactions of which at most one can land.
+pub trait ConflictStrategy {
+    /// Name shown in logs.
+    fn name(&self) -> &'static str;
+
+    /// The hash the transactions must be signed with, if the strategy dictates one.
+    /// `None` means a recent blockhash is fetched.
+    fn required_blockhash(&self) -> Option<Hash> {
+        None
+    }
+
+    /// Builds one signed transaction per entry of `compute_budgets`, in order,
+    /// compiled in `format` and signed with its blockhash.
+    fn construct(
+        &self,
+        sender: &AccountInfo,
+        recipient: &AccountInfo,
+        compute_budgets: &[ComputeBudgetConfig],
+        format: &TransactionFormat,
+    ) -> Result<Vec<ConflictingTransaction>, Box<dyn Error>>;
+}
+
+/// Settings of the balance-drain strategy.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct BalanceDrainConfig {
+    /// Share of the available balance the first transaction transfers, in percent.
+    pub start_percent: f64,
+    /// How much smaller each following transaction's share is, in percent.
+    pub step_percent: f64,
+}
+
+impl Default for BalanceDrainConfig {
+    fn default() -> Self {
+        BalanceDrainConfig {
+            start_percent: 90.0,
+            step_percent: 1.0,
+        }
+    }
+}
+
+/// Settings of the fixed-amount strategy.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct FixedAmountMemoConfig {
+    /// Lamports every transaction transfers. Defaults to the whole available balance;
+    /// must be more than half of it so that only one transfer fits.
+    pub amount_lamports: Option<u64>,
+}
+
+/// How the race transactions are made mutually exclusive.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConflictStrategyConfig {
+    /// Each transaction transfers a decreasing share of the sender's balance.
+    BalanceDrain(BalanceDrainConfig),
+    /// Every transaction advances the same durable nonce account.
+    DurableNonce(DurableNonceConfig),
+    /// Every transaction transfers the same amount and carries a unique memo.
+    FixedAmountMemo(FixedAmountMemoConfig),
+}
+
+impl Default for ConflictStrategyConfig {
+    fn default() -> Self {
+        ConflictStrategyConfig::BalanceDrain(BalanceDrainConfig::default())
+    }
+}
+
+impl ConflictStrategyConfig {
+    /// Checks the settings that can be checked without talking to the network.
+    pub fn validate(&self) -> Result<(), String> {
+        if let ConflictStrategyConfig::BalanceDrain(config) = self {
+            if !(config.start_percent > 0.0 && config.start_percent <= 100.0) {
+                return Err(format!(
+                    "conflict_strategy.start_percent must be in (0, 100], got {}.",
+                    config.start_percent
+                ));
+            }
+            if config.step_percent <= 0.0 {
+                return Err(format!(
+                    "conflict_strategy.step_percent must be positive, got {}.",
+                    config.step_percent
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates the strategy for one race, fetching any on-chain state it depends on.
+    pub async fn build(
+        &self,
+        rpc_client: &RpcClient,
+    ) -> Result<Box<dyn ConflictStrategy>, Box<dyn Error>> {
+        Ok(match self {
+            ConflictStrategyConfig::BalanceDrain(config) => Box::new(BalanceDrainStrategy(*config)),
+            ConflictStrategyConfig::DurableNonce(config) => {
+                println!("Fetching nonce account {}...", config.nonce_account);
+                let nonce = fetch_nonce(rpc_client, &config.nonce_account).await?;
+                println!(
+                    "Using durable nonce: {} (authority: {})",
+                    nonce.blockhash, nonce.authority
+                );
+                Box::new(DurableNonceStrategy {
+                    amount_lamports: config.amount_lamports,
+                    nonce,
+                })
+            }
+            ConflictStrategyConfig::FixedAmountMemo(config) => {
+                Box::new(FixedAmountMemoStrategy(*config))
+            }
+        })
+    }
+}
+
+/// Largest total fee among the transactions, given their shape.
+fn max_fee_lamports(
+    compute_budgets: &[ComputeBudgetConfig],
+    num_signatures: usize,
+    other_instruction_count: usize,
+) -> u64 {
+    compute_budgets
+        .iter()
+        .map(|budget| budget.total_fee_lamports(num_signatures, other_instruction_count))
+        .max()
+        .unwrap_or(0)
+}
+
+/// The sender's balance minus the reserve and the largest fee.
+fn available_lamports(sender: &AccountInfo, max_fee_lamports: u64) -> Result<u64, Box<dyn Error>> {
+    let required_reserve_lamports = MIN_SENDER_RESERVE_LAMPORTS + max_fee_lamports;
+    if sender.balance <= required_reserve_lamports {
+        return Err(format!(
+            "Sender balance ({} lamports) is too low. Must be > {} lamports ({} reserve + {} max fee) to construct transactions.",
+            sender.balance,
+            required_reserve_lamports,
+            MIN_SENDER_RESERVE_LAMPORTS,
+            max_fee_lamports
+        )
+        .into());
+    }
+    Ok(sender.balance - required_reserve_lamports)
+}
+
+/// Prepends the compute budget instructions, then signs with `sender` as fee payer.
+fn sign_transaction(
+    sender: &AccountInfo,
+    extra_signers: &[&Keypair],
+    compute_budget: &ComputeBudgetConfig,
+    leading_instructions: Vec<Instruction>,
+    instructions: Vec<Instruction>,
+    format: &TransactionFormat,
+) -> Result<VersionedTransaction, Box<dyn Error>> {
+    let mut all_instructions = leading_instructions;
+    all_instructions.extend(compute_budget.instructions());
+    all_instructions.extend(instructions);
+
+    let mut signers = vec![&sender.keypair];
+    signers.extend(extra_signers);
+    format.compile_and_sign(&sender.pubkey, &all_instructions, &signers)
+}
+
+/// Transaction `i` transfers `start - step * i` percent of the available balance.
+/// Any two transfers together exceed the balance, so only one can succeed.
+pub struct BalanceDrainStrategy(pub BalanceDrainConfig);
+
+impl BalanceDrainStrategy {
+    fn percent(&self, index: usize) -> f64 {
+        self.0.start_percent - self.0.step_percent * index as f64
+    }
+}
+
+impl ConflictStrategy for BalanceDrainStrategy {
+    fn name(&self) -> &'static str {
+        "balance_drain"
+    }
+
+    fn construct(
+        &self,
+        sender: &AccountInfo,
+        recipient: &AccountInfo,
+        compute_budgets: &[ComputeBudgetConfig],
+        format: &TransactionFormat,
+    ) -> Result<Vec<ConflictingTransaction>, Box<dyn Error>> {
+        let count = compute_budgets.len();
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let smallest = self.percent(count - 1);
+        if smallest <= 0.0 {
+            return Err(format!(
+                "balance_drain (start {}%, step {}%) has no positive share left for transaction {}. Use a smaller step or another conflict strategy.",
+                self.0.start_percent, self.0.step_percent, count - 1
+            )
+            .into());
+        }
+        if count > 1 && smallest + self.percent(count - 2) <= 100.0 {
+            return Err(format!(
+                "balance_drain (start {}%, step {}%) is not mutually exclusive for {} transactions: the two smallest shares add up to {}%. Use a smaller step or another conflict strategy.",
+                self.0.start_percent,
+                self.0.step_percent,
+                count,
+                smallest + self.percent(count - 2)
+            )
+            .into());
+        }
+
+        let available = available_lamports(sender, max_fee_lamports(compute_budgets, 1, 1))?;
+        let mut transactions = Vec::with_capacity(count);
+        for (i, compute_budget) in compute_budgets.iter().enumerate() {
+            let amount_lamports = (available as f64 * self.percent(i) / 100.0) as u64;
+            if amount_lamports == 0 {
+                return Err(format!(
+                    "Transaction {} would transfer 0 lamports ({:.2}% of {} available lamports). Sender balance is too low.",
+                    i,
+                    self.percent(i),
+                    available
+                )
+                .into());
+            }
+            let transaction = sign_transaction(
+                sender,
+                &[],
+                compute_budget,
+                Vec::new(),
+                vec![system_instruction::transfer(
+                    &sender.pubkey,
+                    &recipient.pubkey,
+                    amount_lamports,
+                )],
+                format,
+            )?;
+            transactions.push(ConflictingTransaction {
+                transaction,
+                amount_lamports,
+                fee_lamports: compute_budget.total_fee_lamports(1, 1),
+            });
+        }
+        Ok(transactions)
+    }
+}
+
+/// Every transaction advances the same durable nonce and is signed with the stored
+/// nonce instead of a recent blockhash, so exactly one of them can land regardless
+/// of the sender's balance.
+pub struct DurableNonceStrategy {
+    /// Transaction `i` transfers `amount_lamports + i`, which keeps the signatures distinct.
+    pub amount_lamports: u64,
+    pub nonce: NonceInfo,
+}
+
+impl ConflictStrategy for DurableNonceStrategy {
+    fn name(&self) -> &'static str {
+        "durable_nonce"
+    }
+
+    fn required_blockhash(&self) -> Option<Hash> {
+        Some(self.nonce.blockhash)
+    }
+
+    fn construct(
+        &self,
+        sender: &AccountInfo,
+        recipient: &AccountInfo,
+        compute_budgets: &[ComputeBudgetConfig],
+        format: &TransactionFormat,
+    ) -> Result<Vec<ConflictingTransaction>, Box<dyn Error>> {
+        let authority = nonce_authority(&self.nonce, [sender, recipient])?;
+        let extra_signers: Vec<&Keypair> = if authority.pubkey == sender.pubkey {
+            Vec::new()
+        } else {
+            vec![&authority.keypair]
+        };
+        let num_signatures = 1 + extra_signers.len();
+        // AdvanceNonceAccount plus the transfer.
+        let other_instruction_count = 2;
+
+        let available = available_lamports(
+            sender,
+            max_fee_lamports(compute_budgets, num_signatures, other_instruction_count),
+        )?;
+        let max_amount_lamports =
+            self.amount_lamports + compute_budgets.len().saturating_sub(1) as u64;
+        if max_amount_lamports > available {
+            return Err(format!(
+                "Sender balance ({} lamports) is too low to transfer up to {} lamports ({} available).",
+                sender.balance, max_amount_lamports, available
+            )
+            .into());
+        }
+
+        let mut transactions = Vec::with_capacity(compute_budgets.len());
+        for (i, compute_budget) in compute_budgets.iter().enumerate() {
+            let amount_lamports = self.amount_lamports + i as u64;
+            // AdvanceNonceAccount must be the first instruction.
+            let transaction = sign_transaction(
+                sender,
+                &extra_signers,
+                compute_budget,
+                vec![system_instruction::advance_nonce_account(
+                    &self.nonce.pubkey,
+                    &authority.pubkey,
+                )],
+                vec![system_instruction::transfer(
+                    &sender.pubkey,
+                    &recipient.pubkey,
+                    amount_lamports,
+                )],
+                format,
+            )?;
+            transactions.push(ConflictingTransaction {
+                transaction,
+                amount_lamports,
+                fee_lamports: compute_budget
+                    .total_fee_lamports(num_signatures, other_instruction_count),
+            });
+        }
+        Ok(transactions)
+    }
+}
+
+/// Every transaction transfers the same amount, more than half of the available
+/// balance, and carries a memo with its index to keep the signatures distinct.
+pub struct FixedAmountMemoStrategy(pub FixedAmountMemoConfig);
+
+impl ConflictStrategy for FixedAmountMemoStrategy {
+    fn name(&self) -> &'static str {
+        "fixed_amount_memo"
+    }
+
+    fn construct(
+        &self,
+        sender: &AccountInfo,
+        recipient: &AccountInfo,
+        compute_budgets: &[ComputeBudgetConfig],
+        format: &TransactionFormat,
+    ) -> Result<Vec<ConflictingTransaction>, Box<dyn Error>> {
+        // The memo plus the transfer.
+        let other_instruction_count = 2;
+        let available = available_lamports(
+            sender,
+            max_fee_lamports(compute_budgets, 1, other_instruction_count),
+        )?;
+        let amount_lamports = self.0.amount_lamports.unwrap_or(available);
+        if amount_lamports > available {
+            return Err(format!(
+                "Fixed amount of {} lamports exceeds the {} lamports available to the sender.",
+                amount_lamports, available
+            )
+            .into());
+        }
+        if compute_budgets.len() > 1 && amount_lamports.saturating_mul(2) <= available {
+            return Err(format!(
+                "Fixed amount of {} lamports is not mutually exclusive: two transfers fit in the {} lamports available. Use more than half of the balance.",
+                amount_lamports, available
+            )
+            .into());
+        }
+
+        let mut transactions = Vec::with_capacity(compute_budgets.len());
+        for (i, compute_budget) in compute_budgets.iter().enumerate() {
+            let memo = Instruction {
+                program_id: MEMO_PROGRAM_ID,
+                accounts: Vec::new(),
+                data: format!("usopp-send race tx {}", i).into_bytes(),
+            };
+            let transaction = sign_transaction(
+                sender,
+                &[],
+                compute_budget,
+                Vec::new(),
+                vec![
+                    memo,
+                    system_instruction::transfer(
+                        &sender.pubkey,
+                        &recipient.pubkey,
+                        amount_lamports,
+                    ),
+                ],
+                format,
+            )?;
+            transactions.push(ConflictingTransaction {
+                transaction,
+                amount_lamports,
+                fee_lamports: compute_budget.total_fee_lamports(1, other_instruction_count),
+            });
+        }
+        Ok(transactions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{signature::Signer, system_program};
+    use std::collections::HashSet;
+
+    fn account(balance: u64) -> AccountInfo {
+        let keypair = Keypair::new();
+        AccountInfo {
+            pubkey: keypair.pubkey(),
+            keypair,
+            balance,
+            role: None,
+        }
+    }
+
+    fn assert_distinct_signatures(transactions: &[ConflictingTransaction]) {
+        let signatures: HashSet<_> = transactions
+            .iter()
+            .map(|ct| ct.transaction.signatures[0])
+            .collect();
+        assert_eq!(signatures.len(), transactions.len());
+    }
+
+    #[test]
+    fn test_balance_drain_amounts_decrease() {
+        let (sender, recipient) = (account(1_010_000), account(0));
+        let strategy = BalanceDrainStrategy(BalanceDrainConfig {
+            start_percent: 90.0,
+            step_percent: 10.0,
+        });
+
+        let transactions = strategy
+            .construct(
+                &sender,
+                &recipient,
+                &[ComputeBudgetConfig::default(); 3],
+                &TransactionFormat::legacy(Hash::new_unique()),
+            )
+            .unwrap();
+
+        // 1_010_000 - 5_000 reserve - 5_000 fee = 1_000_000 available.
+        let amounts: Vec<u64> = transactions.iter().map(|ct| ct.amount_lamports).collect();
+        assert_eq!(amounts, vec![900_000, 800_000, 700_000]);
+        assert_distinct_signatures(&transactions);
+    }
+
+    #[test]
+    fn test_balance_drain_rejects_shares_that_fit_twice() {
+        let (sender, recipient) = (account(1_010_000), account(0));
+        let strategy = BalanceDrainStrategy(BalanceDrainConfig {
+            start_percent: 60.0,
+            step_percent: 10.0,
+        });
+        let budgets = [ComputeBudgetConfig::default(); 3];
+
+        assert!(strategy
+            .construct(
+                &sender,
+                &recipient,
+                &budgets[..2],
+                &TransactionFormat::legacy(Hash::new_unique())
+            )
+            .is_ok());
+        let err = strategy
+            .construct(
+                &sender,
+                &recipient,
+                &budgets,
+                &TransactionFormat::legacy(Hash::new_unique()),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("not mutually exclusive"));
+    }
+
+    #[test]
+    fn test_fixed_amount_memo_uses_same_amount_and_distinct_memos() {
+        let (sender, recipient) = (account(1_010_000), account(0));
+        let strategy = FixedAmountMemoStrategy(FixedAmountMemoConfig::default());
+
+        let transactions = strategy
+            .construct(
+                &sender,
+                &recipient,
+                &[ComputeBudgetConfig::default(); 3],
+                &TransactionFormat::legacy(Hash::new_unique()),
+            )
+            .unwrap();
+
+        assert!(transactions
+            .iter()
+            .all(|ct| ct.amount_lamports == 1_000_000));
+        assert_distinct_signatures(&transactions);
+
+        let half = FixedAmountMemoStrategy(FixedAmountMemoConfig {
+            amount_lamports: Some(500_000),
+        });
+        assert!(half
+            .construct(
+                &sender,
+                &recipient,
+                &[ComputeBudgetConfig::default(); 2],
+                &TransactionFormat::legacy(Hash::new_unique())
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_durable_nonce_advances_nonce_first() {
+        let (sender, recipient) = (account(1_000_000), account(0));
+        let nonce_hash = Hash::new_unique();
+        let strategy = DurableNonceStrategy {
+            amount_lamports: 1_000,
+            nonce: NonceInfo {
+                pubkey: Pubkey::new_unique(),
+                authority: recipient.pubkey,
+                blockhash: nonce_hash,
+                lamports: 1_500_000,
+            },
+        };
+        assert_eq!(strategy.required_blockhash(), Some(nonce_hash));
+
+        let transactions = strategy
+            .construct(
+                &sender,
+                &recipient,
+                &[ComputeBudgetConfig::default(); 2],
+                &TransactionFormat::legacy(nonce_hash),
+            )
+            .unwrap();
+
+        assert_distinct_signatures(&transactions);
+        for (i, ct) in transactions.iter().enumerate() {
+            let message = &ct.transaction.message;
+            assert_eq!(*message.recent_blockhash(), nonce_hash);
+            assert_eq!(
+                message.static_account_keys()[message.instructions()[0].program_id_index as usize],
+                system_program::id()
+            );
+            // The recipient is the nonce authority, so it signs too.
+            assert_eq!(message.header().num_required_signatures, 2);
+            assert_eq!(ct.amount_lamports, 1_000 + i as u64);
+            assert_eq!(ct.fee_lamports, 10_000);
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//! Endpoint fixtures shared by the monitoring and slot clock tests.
+
+use crate::endpoint::Endpoint;
+
+/// Nothing listens on port 1, so connections to it are refused.
+const UNREACHABLE: &str = "127.0.0.1:1";
+
+/// An endpoint whose RPC and websocket both refuse connections.
+pub fn unreachable_endpoint(name: &str) -> Endpoint {
+    Endpoint::from_url(name.to_string(), format!("http://{}", UNREACHABLE))
+}
+
+/// An endpoint served from `url` whose websocket refuses connections, so
+/// subscriptions fail.
+pub fn endpoint_without_websocket(name: &str, url: String) -> Endpoint {
+    let mut endpoint = Endpoint::from_url(name.to_string(), url);
+    endpoint.websocket_url = Some(format!("ws://{}", UNREACHABLE));
+    endpoint
+}
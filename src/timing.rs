@@ -0,0 +1,539 @@
+use futures_util::FutureExt;
+use serde::{Deserialize, Serialize};
+use solana_client::client_error::reqwest::Url;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_rustls::{
+    rustls::{ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName},
+    TlsConnector,
+};
+
+/// Phases of one HTTP request, in microseconds. The connection phases are
+/// `None` when the request reused the connection opened by the warm-up.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SendTiming {
+    pub dns_us: Option<u64>,
+    pub connect_us: Option<u64>,
+    pub tls_us: Option<u64>,
+    /// Writing the request until the socket accepted its last byte.
+    pub write_us: u64,
+    /// From the end of the write until the first byte of the response.
+    pub ttfb_us: u64,
+}
+
+trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Connection for T {}
+
+/// A response read off the connection.
+pub struct TimedResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// An HTTP/1.1 client over a single keep-alive connection that it opens and
+/// reads itself, so that every phase of a request can be timed. `reqwest`
+/// hides these phases inside its connection pool.
+pub struct TimedHttpClient {
+    host: String,
+    port: u16,
+    /// `Host` header value: the host, bracketed if IPv6, and any non-default port.
+    host_header: String,
+    tls: bool,
+    path: String,
+    headers: BTreeMap<String, String>,
+    timeout: Duration,
+    connection: tokio::sync::Mutex<Option<Box<dyn Connection>>>,
+    last_timing: Mutex<Option<SendTiming>>,
+    /// Connection phases of the connection the latest `post` went over, which
+    /// the warm-up may have opened.
+    connection_timing: Mutex<Option<SendTiming>>,
+    /// When the latest request's last byte was handed to the socket.
+    last_written_at: Mutex<Option<Instant>>,
+}
+
+impl TimedHttpClient {
+    pub fn new(
+        url: &str,
+        headers: BTreeMap<String, String>,
+        timeout: Duration,
+    ) -> Result<Self, String> {
+        let parsed = Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+        let tls = match parsed.scheme() {
+            "https" => true,
+            "http" => false,
+            scheme => return Err(format!("Unsupported URL scheme '{}'", scheme)),
+        };
+        let host_str = parsed.host_str().ok_or("URL has no host")?;
+        // The URL keeps IPv6 addresses in brackets, DNS lookup and TLS take them bare.
+        let host = host_str.trim_start_matches('[').trim_end_matches(']');
+        // `port()` is only set when the URL names a non-default port.
+        let host_header = match parsed.port() {
+            Some(port) => format!("{}:{}", host_str, port),
+            None => host_str.to_string(),
+        };
+        let path = match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        };
+        Ok(TimedHttpClient {
+            port: parsed.port_or_known_default().unwrap_or(80),
+            host: host.to_string(),
+            host_header,
+            tls,
+            path,
+            headers,
+            timeout,
+            connection: tokio::sync::Mutex::new(None),
+            last_timing: Mutex::new(None),
+            connection_timing: Mutex::new(None),
+            last_written_at: Mutex::new(None),
+        })
+    }
+
+    /// Timing of the latest `post`, including failed ones.
+    pub fn last_timing(&self) -> Option<SendTiming> {
+        *self.last_timing.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// DNS, TCP and TLS setup of the connection the latest `post` used. Unlike
+    /// `last_timing`, this is set when the post reused the warm-up's connection.
+    pub fn connection_timing(&self) -> Option<SendTiming> {
+        *self
+            .connection_timing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// When the latest `post` finished writing its request, if it got that far.
+    pub fn last_written_at(&self) -> Option<Instant> {
+        *self
+            .last_written_at
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// POSTs a JSON body. A kept-alive connection the server has already
+    /// closed is replaced before writing. One that fails while the request is
+    /// written, before any byte of it was accepted, is replaced once. Any
+    /// later failure is returned, since the server may have received the
+    /// request.
+    pub async fn post(&self, body: &[u8]) -> Result<TimedResponse, String> {
+        let mut connection = self.connection.lock().await;
+        let mut timing = SendTiming::default();
+        *self
+            .last_written_at
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = None;
+        if connection.as_mut().is_some_and(is_closed) {
+            *connection = None;
+        }
+        let result = match tokio::time::timeout(self.timeout, async {
+            let reused = connection.is_some();
+            match self.exchange(&mut connection, body, &mut timing).await {
+                Err(ExchangeError::Unsent(_)) if reused => {
+                    *connection = None;
+                    self.exchange(&mut connection, body, &mut timing).await
+                }
+                result => result,
+            }
+        })
+        .await
+        {
+            Ok(result) => result.map_err(ExchangeError::into_message),
+            Err(_) => Err(format!("Request timed out after {:?}", self.timeout)),
+        };
+        if result.is_err() {
+            *connection = None;
+        }
+        *self.last_timing.lock().unwrap_or_else(|e| e.into_inner()) = Some(timing);
+        result
+    }
+
+    async fn exchange(
+        &self,
+        connection: &mut Option<Box<dyn Connection>>,
+        body: &[u8],
+        timing: &mut SendTiming,
+    ) -> Result<TimedResponse, ExchangeError> {
+        let stream = match connection {
+            Some(stream) => stream,
+            None => {
+                let stream = self.connect(timing).await.map_err(ExchangeError::Unsent)?;
+                self.set_connection_timing(*timing);
+                connection.insert(stream)
+            }
+        };
+
+        let mut request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n",
+            self.path,
+            self.host_header,
+            body.len()
+        );
+        for (name, value) in &self.headers {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        request.push_str("\r\n");
+        let mut request = request.into_bytes();
+        request.extend_from_slice(body);
+
+        let start = Instant::now();
+        let mut written = 0;
+        while written < request.len() {
+            let error = match stream.write(&request[written..]).await {
+                Ok(0) => "connection closed".to_string(),
+                Ok(count) => {
+                    written += count;
+                    continue;
+                }
+                Err(e) => e.to_string(),
+            };
+            let message = format!("Failed to write request: {}", error);
+            return Err(if written == 0 {
+                ExchangeError::Unsent(message)
+            } else {
+                ExchangeError::Sent(message)
+            });
+        }
+        stream
+            .flush()
+            .await
+            .map_err(|e| ExchangeError::Sent(format!("Failed to write request: {}", e)))?;
+        timing.write_us = elapsed_us(start);
+        *self
+            .last_written_at
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+
+        let start = Instant::now();
+        let mut buffer = vec![0; 8192];
+        let read = stream
+            .read(&mut buffer)
+            .await
+            .map_err(|e| ExchangeError::Sent(format!("Failed to read response: {}", e)))?;
+        if read == 0 {
+            return Err(ExchangeError::Sent(
+                "Connection closed before the response".to_string(),
+            ));
+        }
+        timing.ttfb_us = elapsed_us(start);
+        buffer.truncate(read);
+
+        let (response, keep_alive) = read_response(stream, buffer)
+            .await
+            .map_err(ExchangeError::Sent)?;
+        if !keep_alive {
+            *connection = None;
+        }
+        Ok(response)
+    }
+
+    /// Opens the connection so that the next `post` can reuse it, and keeps
+    /// its setup timing for that post.
+    pub async fn warm_up(&self) -> Result<(), String> {
+        let mut connection = self.connection.lock().await;
+        let mut timing = SendTiming::default();
+        let stream = tokio::time::timeout(self.timeout, self.connect(&mut timing))
+            .await
+            .map_err(|_| format!("Connection timed out after {:?}", self.timeout))??;
+        *connection = Some(stream);
+        self.set_connection_timing(timing);
+        Ok(())
+    }
+
+    fn set_connection_timing(&self, timing: SendTiming) {
+        *self
+            .connection_timing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(timing);
+    }
+
+    async fn connect(&self, timing: &mut SendTiming) -> Result<Box<dyn Connection>, String> {
+        let start = Instant::now();
+        let address = tokio::net::lookup_host((self.host.as_str(), self.port))
+            .await
+            .map_err(|e| format!("DNS lookup of {} failed: {}", self.host, e))?
+            .next()
+            .ok_or_else(|| format!("DNS lookup of {} returned no address", self.host))?;
+        timing.dns_us = Some(elapsed_us(start));
+
+        let start = Instant::now();
+        let stream = TcpStream::connect(address)
+            .await
+            .map_err(|e| format!("TCP connect to {} failed: {}", self.host, e))?;
+        stream.set_nodelay(true).map_err(|e| e.to_string())?;
+        timing.connect_us = Some(elapsed_us(start));
+
+        if !self.tls {
+            return Ok(Box::new(stream));
+        }
+        let server_name = ServerName::try_from(self.host.as_str())
+            .map_err(|e| format!("Invalid TLS server name {}: {}", self.host, e))?;
+        let start = Instant::now();
+        let stream = tls_connector()
+            .connect(server_name, stream)
+            .await
+            .map_err(|e| format!("TLS handshake with {} failed: {}", self.host, e))?;
+        timing.tls_us = Some(elapsed_us(start));
+        Ok(Box::new(stream))
+    }
+}
+
+/// Why an exchange failed, as far as the server is concerned.
+enum ExchangeError {
+    /// No byte of the request was accepted, so it is safe to send again.
+    Unsent(String),
+    /// The server may have received the request.
+    Sent(String),
+}
+
+impl ExchangeError {
+    fn into_message(self) -> String {
+        match self {
+            ExchangeError::Unsent(message) | ExchangeError::Sent(message) => message,
+        }
+    }
+}
+
+/// Whether the server has closed a kept-alive connection, or sent something
+/// unsolicited on it, either of which makes it unusable. Does not wait.
+fn is_closed(stream: &mut Box<dyn Connection>) -> bool {
+    let mut byte = [0; 1];
+    stream.read(&mut byte).now_or_never().is_some()
+}
+
+fn elapsed_us(start: Instant) -> u64 {
+    start.elapsed().as_micros() as u64
+}
+
+fn tls_connector() -> TlsConnector {
+    let mut roots = RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+/// Reads the rest of a response whose first bytes are in `buffer`. Returns it
+/// along with whether the server keeps the connection open.
+async fn read_response(
+    stream: &mut Box<dyn Connection>,
+    mut buffer: Vec<u8>,
+) -> Result<(TimedResponse, bool), String> {
+    let mut chunk = [0; 8192];
+    let (status, header_len, content_length, chunked, keep_alive) = loop {
+        let mut headers = [httparse::EMPTY_HEADER; 64];
+        let mut response = httparse::Response::new(&mut headers);
+        match response.parse(&buffer) {
+            Ok(httparse::Status::Complete(header_len)) => {
+                let header = |name: &str| {
+                    response
+                        .headers
+                        .iter()
+                        .find(|h| h.name.eq_ignore_ascii_case(name))
+                        .map(|h| String::from_utf8_lossy(h.value).to_ascii_lowercase())
+                };
+                let content_length = header("content-length").and_then(|v| v.trim().parse().ok());
+                let chunked = header("transfer-encoding").is_some_and(|v| v.contains("chunked"));
+                let keep_alive = header("connection").is_none_or(|v| !v.contains("close"));
+                break (
+                    response.code.unwrap_or_default(),
+                    header_len,
+                    content_length,
+                    chunked,
+                    keep_alive,
+                );
+            }
+            Ok(httparse::Status::Partial) => {}
+            Err(e) => return Err(format!("Invalid HTTP response: {}", e)),
+        }
+        let read = read_more(stream, &mut chunk).await?;
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+    let mut body = buffer.split_off(header_len);
+
+    if chunked {
+        loop {
+            if let Some(decoded) = decode_chunked(&body)? {
+                return Ok((
+                    TimedResponse {
+                        status,
+                        body: decoded,
+                    },
+                    keep_alive,
+                ));
+            }
+            let read = read_more(stream, &mut chunk).await?;
+            body.extend_from_slice(&chunk[..read]);
+        }
+    }
+    match content_length {
+        Some(length) => {
+            while body.len() < length {
+                let read = read_more(stream, &mut chunk).await?;
+                body.extend_from_slice(&chunk[..read]);
+            }
+            body.truncate(length);
+            Ok((TimedResponse { status, body }, keep_alive))
+        }
+        // Without a length, the body ends with the connection.
+        None => {
+            stream
+                .read_to_end(&mut body)
+                .await
+                .map_err(|e| format!("Failed to read response: {}", e))?;
+            Ok((TimedResponse { status, body }, false))
+        }
+    }
+}
+
+async fn read_more(stream: &mut Box<dyn Connection>, chunk: &mut [u8]) -> Result<usize, String> {
+    match stream.read(chunk).await {
+        Ok(0) => Err("Connection closed mid-response".to_string()),
+        Ok(read) => Ok(read),
+        Err(e) => Err(format!("Failed to read response: {}", e)),
+    }
+}
+
+/// Decodes a chunked body, or returns `None` if it is not complete yet.
+/// Trailers are not supported.
+fn decode_chunked(mut data: &[u8]) -> Result<Option<Vec<u8>>, String> {
+    let mut decoded = Vec::new();
+    loop {
+        let Some(line_end) = data.windows(2).position(|w| w == b"\r\n") else {
+            return Ok(None);
+        };
+        let size_line = String::from_utf8_lossy(&data[..line_end]);
+        let size_hex = size_line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size_hex, 16)
+            .map_err(|_| format!("Invalid chunk size '{}'", size_hex))?;
+        data = &data[line_end + 2..];
+        if data.len() < size + 2 {
+            return Ok(None);
+        }
+        if size == 0 {
+            return Ok(Some(decoded));
+        }
+        decoded.extend_from_slice(&data[..size]);
+        data = &data[size + 2..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_chunked() {
+        assert_eq!(
+            decode_chunked(b"5\r\nhello\r\n6;x=y\r\n world\r\n0\r\n\r\n").unwrap(),
+            Some(b"hello world".to_vec())
+        );
+        assert_eq!(decode_chunked(b"5\r\nhel").unwrap(), None);
+        assert!(decode_chunked(b"zz\r\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_post_times_connection_phases_only_when_not_reused() {
+        let (url, requests) = crate::send_path::mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            "{\"result\":1}".to_string(),
+        )]);
+        let client = TimedHttpClient::new(&url, BTreeMap::new(), Duration::from_secs(5)).unwrap();
+
+        let response = client.post(b"{\"id\":1}").await.unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, b"{\"result\":1}");
+        assert_eq!(requests.recv().unwrap(), "{\"id\":1}");
+        let timing = client.last_timing().unwrap();
+        assert!(timing.dns_us.is_some());
+        assert!(timing.connect_us.is_some());
+        assert_eq!(timing.tls_us, None);
+        assert_eq!(client.connection_timing().unwrap().dns_us, timing.dns_us);
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_connection_timing_carries_over_to_the_post() {
+        let (url, _requests) = crate::send_path::mock_http::serve(vec![(
+            "HTTP/1.1 200 OK",
+            "{\"result\":1}".to_string(),
+        )]);
+        let client = TimedHttpClient::new(&url, BTreeMap::new(), Duration::from_secs(5)).unwrap();
+
+        client.warm_up().await.unwrap();
+        client.post(b"{\"id\":1}").await.unwrap();
+
+        assert_eq!(client.last_timing().unwrap().dns_us, None);
+        let connection = client.connection_timing().unwrap();
+        assert!(connection.dns_us.is_some());
+        assert!(connection.connect_us.is_some());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_post_is_not_retried_once_the_request_was_sent() {
+        use std::io::Read;
+
+        // Reads each request, then closes the connection without answering.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let (request_tx, requests) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut buffer = [0; 1024];
+                if stream.unwrap().read(&mut buffer).is_ok_and(|read| read > 0) {
+                    let _ = request_tx.send(());
+                }
+            }
+        });
+        let client = TimedHttpClient::new(&url, BTreeMap::new(), Duration::from_secs(5)).unwrap();
+
+        client.warm_up().await.unwrap();
+        let error = client
+            .post(b"{\"id\":1}")
+            .await
+            .err()
+            .expect("the server never answers");
+
+        assert_eq!(error, "Connection closed before the response");
+        assert!(client.last_written_at().is_some());
+        assert_eq!(requests.recv_timeout(Duration::from_secs(1)), Ok(()));
+        assert!(requests.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn test_host_header_keeps_non_default_port_and_brackets_ipv6() {
+        let header = |url: &str| {
+            TimedHttpClient::new(url, BTreeMap::new(), Duration::from_secs(5))
+                .unwrap()
+                .host_header
+        };
+        assert_eq!(header("https://rpc.example.com/"), "rpc.example.com");
+        assert_eq!(header("https://rpc.example.com:443/"), "rpc.example.com");
+        assert_eq!(
+            header("http://rpc.example.com:8899/"),
+            "rpc.example.com:8899"
+        );
+        assert_eq!(header("http://[::1]:8899/"), "[::1]:8899");
+
+        let client =
+            TimedHttpClient::new("http://[::1]/", BTreeMap::new(), Duration::from_secs(5)).unwrap();
+        assert_eq!(client.host, "::1");
+    }
+}
@@ -0,0 +1,158 @@
+use crate::endpoint::{redact_client_error, Endpoint};
+use crate::send_path::{block_on, SendPath, Submit, WireTransaction};
+use serde::Deserialize;
+use solana_client::nonblocking::tpu_client::{TpuClient, TpuSenderError};
+use solana_client::tpu_client::{TpuClientConfig, DEFAULT_FANOUT_SLOTS, MAX_FANOUT_SLOTS};
+use solana_quic_client::{QuicConfig, QuicConnectionManager, QuicPool};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::sync::Arc;
+
+/// Settings of a path that sends straight to the leaders' TPU over QUIC.
+///
+/// The endpoint's `url` is only used to look up the leader schedule and the
+/// leaders' TPU addresses, and to monitor the transaction afterwards.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct TpuPathConfig {
+    /// Websocket used to follow slot updates. Defaults to the endpoint's
+    /// websocket URL.
+    pub websocket_url: Option<String>,
+    /// Number of upcoming leaders to send to, including the current one.
+    pub fanout_slots: Option<u64>,
+}
+
+/// A connected QUIC client that forwards transactions to upcoming leaders.
+pub struct TpuPath {
+    client: TpuClient<QuicPool, QuicConnectionManager, QuicConfig>,
+}
+
+impl TpuPath {
+    /// Fetches the leader schedule and cluster contact info through the endpoint
+    /// and subscribes to slot updates, on the shared multi-threaded Tokio runtime.
+    pub fn connect(endpoint: &Endpoint, config: &TpuPathConfig) -> Result<Self, String> {
+        let websocket_url = config
+            .websocket_url
+            .clone()
+            .unwrap_or_else(|| endpoint.websocket_url());
+        let fanout_slots = config
+            .fanout_slots
+            .unwrap_or(DEFAULT_FANOUT_SLOTS)
+            .clamp(1, MAX_FANOUT_SLOTS);
+        let rpc_client = Arc::new(endpoint.nonblocking_rpc_client(CommitmentConfig::confirmed()));
+        block_on(TpuClient::new(
+            "usopp-tpu",
+            rpc_client,
+            &websocket_url,
+            TpuClientConfig { fanout_slots },
+        ))
+        .map(|client| TpuPath { client })
+        .map_err(|e| {
+            let error = match e {
+                TpuSenderError::RpcError(e) => redact_client_error(e),
+                e => e.to_string(),
+            };
+            format!(
+                "Failed to connect TPU client for endpoint '{}': {}",
+                endpoint.name, error
+            )
+        })
+    }
+}
+
+impl SendPath for TpuPath {
+    /// Sends to the current and upcoming leaders. Succeeds as soon as one leader
+    /// accepted the transaction; whether it lands is up to monitoring.
+    fn submit<'a>(&'a self, transaction: &'a WireTransaction) -> Submit<'a> {
+        Box::pin(async move {
+            self.client
+                .try_send_wire_transaction(transaction.bytes.clone())
+                .await
+                .map(|()| transaction.signature.into())
+                .map_err(redact_client_error)
+        })
+    }
+
+    /// Calls `getSlot` on the endpoint, then opens the QUIC connections to the
+    /// upcoming leaders: the connection cache treats an empty payload as a warm-up
+    /// and sends nothing.
+    fn warm_up(&self) -> Option<Result<(), String>> {
+        Some(block_on(async {
+            self.client
+                .rpc_client()
+                .get_slot()
+                .await
+                .map_err(redact_client_error)?;
+            self.client
+                .try_send_wire_transaction(Vec::new())
+                .await
+                .map_err(|e| {
+                    format!(
+                        "Failed to open leader connections: {}",
+                        redact_client_error(e)
+                    )
+                })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transactions::TransactionFormat;
+    use solana_sdk::{
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_instruction,
+    };
+    use std::time::Duration;
+
+    /// Run with `solana-test-validator` up:
+    /// `cargo test -- --ignored test_tpu_send_lands_on_local_test_validator`
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "requires a running solana-test-validator (USOPP_TEST_VALIDATOR_URL, default http://127.0.0.1:8899)"]
+    async fn test_tpu_send_lands_on_local_test_validator() {
+        let url = std::env::var("USOPP_TEST_VALIDATOR_URL")
+            .unwrap_or_else(|_| "http://127.0.0.1:8899".to_string());
+        let endpoint = Endpoint::from_url("local-tpu".to_string(), url);
+        let rpc_client = endpoint.nonblocking_rpc_client(CommitmentConfig::confirmed());
+
+        let payer = Keypair::new();
+        let airdrop = rpc_client
+            .request_airdrop(&payer.pubkey(), 1_000_000_000)
+            .await
+            .unwrap();
+        rpc_client.poll_for_signature(&airdrop).await.unwrap();
+
+        let transaction =
+            TransactionFormat::legacy(rpc_client.get_latest_blockhash().await.unwrap())
+                .compile_and_sign(
+                    &payer.pubkey(),
+                    &[system_instruction::transfer(
+                        &payer.pubkey(),
+                        &Pubkey::new_unique(),
+                        10_000_000,
+                    )],
+                    &[&payer],
+                )
+                .unwrap();
+
+        let path = TpuPath::connect(&endpoint, &TpuPathConfig::default()).unwrap();
+        assert_eq!(path.warm_up(), Some(Ok(())));
+        let wire_transaction = WireTransaction {
+            bytes: bincode::serialize(&transaction).unwrap(),
+            signature: transaction.signatures[0],
+            tip: None,
+        };
+        let signature = path.submit(&wire_transaction).await.unwrap().signature;
+        assert_eq!(signature, transaction.signatures[0]);
+
+        for _ in 0..30 {
+            if let Some(status) = rpc_client.get_signature_status(&signature).await.unwrap() {
+                assert!(status.is_ok());
+                return;
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        panic!("transaction sent over TPU did not land within 30 seconds");
+    }
+}
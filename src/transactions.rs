@@ -1,135 +1,483 @@
 use crate::accounts::AccountInfo;
-use solana_client::rpc_client::RpcClient;
+use crate::bundle::construct_tip_transaction;
+use crate::endpoint::{redact_client_error, Endpoint};
+use crate::fees::ComputeBudgetConfig;
+use crate::send_path::{SendOptionsConfig, SendOutcome, SendPathConfig, WireTransaction};
+use crate::strategy::ConflictStrategyConfig;
+use crate::timing::SendTiming;
+use serde::Deserialize;
+use solana_client::nonblocking;
 use solana_client::rpc_response::RpcSimulateTransactionResult;
 use solana_sdk::{
-    message::Message, signature::Signature, system_instruction, transaction::Transaction,
+    address_lookup_table::{state::AddressLookupTable, AddressLookupTableAccount},
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, Message, VersionedMessage},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    system_instruction,
+    transaction::{Transaction, VersionedTransaction},
 };
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread as std_thread;
-use std::{error::Error, time::Instant};
-use tokio::runtime::Builder as TokioRuntimeBuilder;
+use std::{
+    error::Error,
+    time::{Duration, Instant, SystemTime},
+};
+use tokio::runtime::Handle as RuntimeHandle;
 use tokio::sync::oneshot;
 
-// Minimum balance to leave in sender's account after a transaction, in lamports.
-const MIN_SENDER_RESERVE_LAMPORTS: u64 = 5_000; // Default rent-exempt minimum + a bit
+/// Message format the race transactions are compiled to.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionVersion {
+    #[default]
+    Legacy,
+    V0,
+}
+
+/// The `transaction_format` configuration section.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionFormatConfig {
+    #[serde(default)]
+    pub version: TransactionVersion,
+    /// Lookup table v0 messages may load accounts from. Only valid with `"version": "v0"`.
+    #[serde(
+        default,
+        deserialize_with = "crate::config::deserialize_optional_pubkey"
+    )]
+    pub address_lookup_table: Option<Pubkey>,
+}
+
+/// Compiles and signs race transactions in the configured format.
+pub struct TransactionFormat {
+    pub version: TransactionVersion,
+    pub blockhash: Hash,
+    pub lookup_tables: Vec<AddressLookupTableAccount>,
+}
+
+impl TransactionFormat {
+    /// A legacy format without lookup tables.
+    #[cfg(test)]
+    pub fn legacy(blockhash: Hash) -> Self {
+        TransactionFormat {
+            version: TransactionVersion::Legacy,
+            blockhash,
+            lookup_tables: Vec::new(),
+        }
+    }
+
+    /// Compiles `instructions` with `payer` as fee payer and signs with `signers`,
+    /// the first of which must be the payer.
+    pub fn compile_and_sign(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<VersionedTransaction, Box<dyn Error>> {
+        let message =
+            match self.version {
+                TransactionVersion::Legacy => VersionedMessage::Legacy(
+                    Message::new_with_blockhash(instructions, Some(payer), &self.blockhash),
+                ),
+                TransactionVersion::V0 => VersionedMessage::V0(v0::Message::try_compile(
+                    payer,
+                    instructions,
+                    &self.lookup_tables,
+                    self.blockhash,
+                )?),
+            };
+        Ok(VersionedTransaction::try_new(message, signers)?)
+    }
+}
+
+/// Fetches an address lookup table so v0 messages can reference its addresses.
+pub async fn fetch_address_lookup_table(
+    rpc_client: &nonblocking::rpc_client::RpcClient,
+    pubkey: &Pubkey,
+) -> Result<AddressLookupTableAccount, Box<dyn Error>> {
+    let account = rpc_client.get_account(pubkey).await.map_err(|e| {
+        format!(
+            "Failed to fetch address lookup table {}: {}",
+            pubkey,
+            redact_client_error(e)
+        )
+    })?;
+    let table = AddressLookupTable::deserialize(&account.data)
+        .map_err(|e| format!("Account {} is not an address lookup table: {}", pubkey, e))?;
+    Ok(AddressLookupTableAccount {
+        key: *pubkey,
+        addresses: table.addresses.to_vec(),
+    })
+}
 
 /// Represents a signed transaction ready to be sent to a specific RPC node.
 #[derive(Debug)]
 pub struct PreparedTransaction {
-    pub rpc_url: String,
-    pub transaction: Transaction,
+    pub endpoint: Endpoint,
+    pub transaction: VersionedTransaction,
     pub signature: Signature,
     pub amount_lamports: u64,
+    pub compute_budget: ComputeBudgetConfig,
+    /// Signature plus prioritization fee the transaction pays if it lands.
+    pub fee_lamports: u64,
+    /// Tip transfer submitted alongside the transaction by bundle paths.
+    pub tip_transaction: Option<VersionedTransaction>,
+    /// Block height after which the blockhash expires; `None` with a durable nonce.
+    pub last_valid_block_height: Option<u64>,
+    /// Position in the race's dispatch order, 0 being first.
+    pub position: usize,
 }
 
 /// Holds the result of a single transaction send attempt.
 #[derive(Debug, Clone)]
 pub struct SendAttempt {
-    pub rpc_url: String,
+    pub endpoint: Endpoint,
     pub original_signature: Signature,
     pub amount_lamports: u64,
+    pub compute_budget: ComputeBudgetConfig,
+    pub fee_lamports: u64,
     pub send_result: Result<Signature, String>,
+    /// ID the block engine assigned to the bundle, for bundle paths.
+    pub bundle_id: Option<String>,
+    /// What `getBundleStatuses` reported after monitoring, for bundle paths.
+    pub bundle_status: Option<String>,
+    /// Effective `sendTransaction` options, for RPC paths.
+    pub send_options: Option<SendOptionsConfig>,
+    /// Sends after the initial one, when rebroadcasting.
+    pub rebroadcasts: u32,
+    /// The send that landed, 0 being the initial one, when rebroadcasting.
+    pub landed_send: Option<u32>,
+    /// Round trip of the request that warmed the path's connection, if it has one.
+    pub warm_up_ms: Option<u128>,
+    pub warm_up_error: Option<String>,
+    /// Dispatch instant: when the request was written, for paths that report
+    /// it, otherwise when the thread handed the transaction to its path right
+    /// after the start gate opened.
     pub send_start_instant: Instant,
+    /// Whether `send_start_instant` is the request write.
+    pub dispatch_at_write: bool,
+    /// When the thread handed the transaction to its path, for every path.
+    pub submit_start_instant: Instant,
+    /// Wall-clock time of the dispatch, for persisted history.
+    pub send_start_time: SystemTime,
     pub send_duration_ms: u128,
+    /// DNS, connect, TLS, write and first-byte phases of the send, for paths that time them.
+    pub timing: Option<SendTiming>,
+    /// DNS, connect and TLS of the connection the send went over, even when the
+    /// warm-up opened it.
+    pub connection_timing: Option<SendTiming>,
+    /// Position in the race's dispatch order, 0 being first.
+    pub position: usize,
+    /// Cluster slot at dispatch, as seen from the race's slot clock.
+    pub dispatch_slot: Option<u64>,
+    /// `false` when the thread missed the start gate and never sent.
+    pub dispatched: bool,
+}
+
+#[cfg(test)]
+impl SendAttempt {
+    /// An attempt that `endpoint` accepted just now, for tests.
+    pub fn accepted(endpoint: Endpoint) -> Self {
+        SendAttempt {
+            endpoint,
+            original_signature: Signature::default(),
+            amount_lamports: 1,
+            compute_budget: ComputeBudgetConfig::default(),
+            fee_lamports: 5000,
+            send_result: Ok(Signature::default()),
+            bundle_id: None,
+            bundle_status: None,
+            send_options: None,
+            rebroadcasts: 0,
+            landed_send: None,
+            warm_up_ms: None,
+            warm_up_error: None,
+            send_start_instant: Instant::now(),
+            dispatch_at_write: false,
+            submit_start_instant: Instant::now(),
+            send_start_time: SystemTime::now(),
+            send_duration_ms: 1,
+            timing: None,
+            connection_timing: None,
+            position: 0,
+            dispatch_slot: None,
+            dispatched: true,
+        }
+    }
 }
 
 /// Holds the result of a single transaction simulation attempt.
 #[derive(Debug)]
 pub struct SimulationAttempt {
-    pub rpc_url: String,
+    pub endpoint: Endpoint,
     pub original_signature: Signature,
     pub amount_lamports: u64,
     pub simulation_result: Result<RpcSimulateTransactionResult, String>,
     pub simulation_duration_ms: u128,
 }
 
-/// Constructs `n` conflicting transfer transactions.
-/// `n` is determined by the number of `rpc_urls`.
-/// Each transaction attempts to send a decreasing percentage of the sender's balance.
-pub fn construct_conflicting_transactions(
+/// Constructs one conflicting transaction per endpoint using the configured `strategy`.
+/// Transactions carry the endpoint's compute budget, falling back to `global_compute_budget`.
+pub async fn construct_conflicting_transactions(
     sender_account: &AccountInfo,
     recipient_account: &AccountInfo,
-    rpc_urls: &[String],
-    rpc_client: &RpcClient,
+    endpoints: &[Endpoint],
+    global_compute_budget: ComputeBudgetConfig,
+    strategy: &ConflictStrategyConfig,
+    format_config: &TransactionFormatConfig,
+    rpc_client: &nonblocking::rpc_client::RpcClient,
 ) -> Result<Vec<PreparedTransaction>, Box<dyn Error>> {
-    if rpc_urls.is_empty() {
-        return Err("No RPC URLs provided for transaction construction.".into());
-    }
-    if sender_account.balance <= MIN_SENDER_RESERVE_LAMPORTS {
-        return Err(format!(
-            "Sender balance ({} lamports) is too low. Must be > {} lamports to construct transactions.",
-            sender_account.balance,
-            MIN_SENDER_RESERVE_LAMPORTS
-        ).into());
+    if endpoints.is_empty() {
+        return Err("No endpoints provided for transaction construction.".into());
     }
 
-    println!("Fetching a recent blockhash...");
-    let recent_blockhash = rpc_client.get_latest_blockhash()?;
-    println!("Using blockhash: {}", recent_blockhash);
-
-    let mut prepared_transactions = Vec::new();
-
-    let max_transferable_balance = sender_account
-        .balance
-        .saturating_sub(MIN_SENDER_RESERVE_LAMPORTS);
-
-    for (i, rpc_url) in rpc_urls.iter().enumerate() {
-        let percentage = 0.90 - (0.01 * i as f64);
-        if percentage <= 0.0 {
-            println!(
-                "Skipping transaction {} as percentage ({:.2}%) is too low or negative.",
-                i,
-                percentage * 100.0
-            );
-            continue;
+    let strategy = strategy.build(rpc_client).await?;
+    println!("Using conflict strategy: {}", strategy.name());
+    let (blockhash, last_valid_block_height) = match strategy.required_blockhash() {
+        Some(blockhash) => (blockhash, None),
+        None => {
+            println!("Fetching a recent blockhash...");
+            let (blockhash, last_valid_block_height) = rpc_client
+                .get_latest_blockhash_with_commitment(rpc_client.commitment())
+                .await
+                .map_err(redact_client_error)?;
+            (blockhash, Some(last_valid_block_height))
         }
+    };
+    println!("Using blockhash: {}", blockhash);
 
-        let amount_lamports = (max_transferable_balance as f64 * percentage) as u64;
-
-        if amount_lamports == 0 {
-            println!(
-                "Skipping transaction {} for RPC {} as calculated amount is 0 lamports (percentage: {:.2}% of {} available lamports).",
-                i, rpc_url, percentage * 100.0, max_transferable_balance
-            );
-            continue;
+    let mut lookup_tables = Vec::new();
+    if let Some(table) = &format_config.address_lookup_table {
+        println!("Fetching address lookup table {}...", table);
+        lookup_tables.push(fetch_address_lookup_table(rpc_client, table).await?);
+    }
+    println!(
+        "Compiling {:?} transactions{}",
+        format_config.version,
+        if lookup_tables.is_empty() {
+            String::new()
+        } else {
+            format!(" with {} lookup table(s)", lookup_tables.len())
         }
+    );
+    let format = TransactionFormat {
+        version: format_config.version,
+        blockhash,
+        lookup_tables,
+    };
+
+    let compute_budgets: Vec<ComputeBudgetConfig> = endpoints
+        .iter()
+        .map(|endpoint| endpoint.compute_budget.or(global_compute_budget))
+        .collect();
+    let conflicting_transactions =
+        strategy.construct(sender_account, recipient_account, &compute_budgets, &format)?;
+
+    // Tips must not depend on a durable nonce, so they always use a recent blockhash.
+    let needs_tip = endpoints
+        .iter()
+        .any(|endpoint| matches!(endpoint.send_path, SendPathConfig::Bundle(_)));
+    let tip_blockhash = if needs_tip && strategy.required_blockhash().is_some() {
+        rpc_client
+            .get_latest_blockhash()
+            .await
+            .map_err(redact_client_error)?
+    } else {
+        blockhash
+    };
+
+    let mut prepared = Vec::with_capacity(endpoints.len());
+    for (position, ((endpoint, compute_budget), conflicting)) in endpoints
+        .iter()
+        .zip(compute_budgets)
+        .zip(conflicting_transactions)
+        .enumerate()
+    {
+        let tip_transaction = match &endpoint.send_path {
+            SendPathConfig::Bundle(config) => {
+                let required = conflicting.amount_lamports
+                    + conflicting.fee_lamports
+                    + config.tip_cost_lamports();
+                if required > sender_account.balance {
+                    return Err(format!(
+                        "Sender balance {} lamports cannot cover the transfer, fee and bundle tip of endpoint '{}' ({} lamports).",
+                        sender_account.balance, endpoint.name, required
+                    )
+                    .into());
+                }
+                println!(
+                    "Bundle tip for endpoint '{}': {} lamports to {}",
+                    endpoint.name, config.tip_lamports, config.tip_account
+                );
+                Some(construct_tip_transaction(
+                    sender_account,
+                    config,
+                    tip_blockhash,
+                ))
+            }
+            _ => None,
+        };
+        prepared.push(PreparedTransaction {
+            endpoint: endpoint.clone(),
+            signature: conflicting.transaction.signatures[0],
+            transaction: conflicting.transaction,
+            amount_lamports: conflicting.amount_lamports,
+            compute_budget,
+            fee_lamports: conflicting.fee_lamports,
+            tip_transaction,
+            last_valid_block_height,
+            position,
+        });
+    }
+    Ok(prepared)
+}
 
-        println!(
-            "Constructing transaction {} for RPC: {}. Amount: {} lamports ({:.2}% of available {} lamports).",
-            i,
-            rpc_url,
-            amount_lamports,
-            percentage * 100.0,
-            max_transferable_balance
-        );
+/// Constructs a transfer of `source`'s whole balance, minus the fee, to `destination`.
+///
+/// Returns `None` when the balance does not cover the fee.
+pub async fn construct_sweep_transaction(
+    source: &AccountInfo,
+    destination: &AccountInfo,
+    rpc_client: &nonblocking::rpc_client::RpcClient,
+) -> Result<Option<(Transaction, u64)>, Box<dyn Error>> {
+    let recent_blockhash = rpc_client
+        .get_latest_blockhash()
+        .await
+        .map_err(redact_client_error)?;
+    let fee_probe = Message::new_with_blockhash(
+        &[system_instruction::transfer(
+            &source.pubkey,
+            &destination.pubkey,
+            source.balance,
+        )],
+        Some(&source.pubkey),
+        &recent_blockhash,
+    );
+    let fee = rpc_client
+        .get_fee_for_message(&fee_probe)
+        .await
+        .map_err(redact_client_error)?;
+    if source.balance <= fee {
+        return Ok(None);
+    }
 
-        let transfer_instruction = system_instruction::transfer(
-            &sender_account.pubkey,
-            &recipient_account.pubkey,
+    let amount_lamports = source.balance - fee;
+    let message = Message::new_with_blockhash(
+        &[system_instruction::transfer(
+            &source.pubkey,
+            &destination.pubkey,
             amount_lamports,
-        );
+        )],
+        Some(&source.pubkey),
+        &recent_blockhash,
+    );
+    let mut transaction = Transaction::new_unsigned(message);
+    transaction.try_sign(&[&source.keypair], recent_blockhash)?;
+    Ok(Some((transaction, amount_lamports)))
+}
 
-        let message = Message::new(&[transfer_instruction], Some(&sender_account.pubkey));
-        let mut transaction = Transaction::new_unsigned(message);
+/// How long Phase 1 waits for the sender threads to connect and warm up.
+const WARM_UP_TIMEOUT_SECONDS: u64 = 30;
 
-        transaction.try_sign(&[&sender_account.keypair], recent_blockhash)?;
-        let signature = transaction.signatures[0];
+/// Holds sender threads back until every one of them is ready, then releases
+/// them all at once.
+struct StartGate {
+    state: Mutex<GateState>,
+    condvar: Condvar,
+}
 
-        prepared_transactions.push(PreparedTransaction {
-            rpc_url: rpc_url.clone(),
-            transaction,
-            signature,
-            amount_lamports,
-        });
+#[derive(Default)]
+struct GateState {
+    opened: bool,
+    arrived: usize,
+}
+
+impl StartGate {
+    fn new() -> Self {
+        StartGate {
+            state: Mutex::new(GateState::default()),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Registers a thread at the gate. Returns `false` if the gate already
+    /// opened, in which case the thread missed the start.
+    fn arrive(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.opened {
+            return false;
+        }
+        state.arrived += 1;
+        true
+    }
+
+    fn wait(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        while !state.opened {
+            state = self.condvar.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
     }
 
-    if prepared_transactions.is_empty() && !rpc_urls.is_empty() {
-        return Err("Failed to construct any transactions. Sender balance might be too low or percentages resulted in zero amounts.".into());
+    /// Releases the waiting threads and returns how many arrived in time.
+    fn open(&self) -> usize {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.opened = true;
+        self.condvar.notify_all();
+        state.arrived
     }
+}
+
+/// Time between the first and the last dispatch of a race, measured on the
+/// monotonic clock. Attempts that missed the start gate are left out. `None`
+/// with fewer than two dispatched attempts.
+///
+/// When only some paths report their request write, every attempt is measured
+/// from the start of its submission instead, which all paths record.
+pub fn dispatch_skew(attempts: &[SendAttempt]) -> Option<Duration> {
+    let mixed = mixed_dispatch_reference(attempts);
+    let dispatched: Vec<Instant> = attempts
+        .iter()
+        .filter(|a| a.dispatched)
+        .map(|a| {
+            if mixed {
+                a.submit_start_instant
+            } else {
+                a.send_start_instant
+            }
+        })
+        .collect();
+    let first = dispatched.iter().min()?;
+    let last = dispatched.iter().max()?;
+    (dispatched.len() > 1).then(|| *last - *first)
+}
+
+/// Whether a race dispatched with `skew` is unfair under `max_dispatch_skew_us`.
+pub fn is_unfair(skew: Option<Duration>, max_dispatch_skew_us: u64) -> bool {
+    skew.is_some_and(|skew| skew.as_micros() > max_dispatch_skew_us as u128)
+}
 
-    Ok(prepared_transactions)
+/// Whether some dispatched attempts were timed at the request write and others
+/// at the start of their submission, so that their dispatch instants are not
+/// comparable.
+pub fn mixed_dispatch_reference(attempts: &[SendAttempt]) -> bool {
+    let mut dispatched = attempts.iter().filter(|a| a.dispatched);
+    dispatched
+        .next()
+        .is_some_and(|first| dispatched.any(|a| a.dispatch_at_write != first.dispatch_at_write))
 }
 
-/// Asynchronously sends a list of prepared transactions to their respective RPC URLs.
+/// Sends every prepared transaction through its endpoint's send path, each from
+/// its own system thread.
+///
+/// The threads are dedicated to one send so that they can block at the start
+/// gate and submit the moment it opens, but they have no runtime of their own:
+/// they enter the caller's runtime, whose workers drive all network I/O.
+///
+/// Phase 1 hands each thread its transaction and lets it connect and warm up
+/// its path. Phase 2 releases all threads at once through a start gate, so
+/// that no endpoint gets a head start from its position in the configuration.
 pub async fn send_transactions_concurrently(
     prepared_transactions_input: Vec<PreparedTransaction>,
 ) -> Vec<SendAttempt> {
@@ -144,142 +492,167 @@ pub async fn send_transactions_concurrently(
         num_transactions
     );
 
-    let mut thread_setups = Vec::with_capacity(num_transactions);
-
-    for i in 0..num_transactions {
-        let rpc_url_for_thread_logging = prepared_transactions_input[i].rpc_url.clone();
+    let runtime = RuntimeHandle::current();
+    let gate = Arc::new(StartGate::new());
+    let mut result_collectors = Vec::with_capacity(num_transactions);
+    let mut handles_to_join = Vec::with_capacity(num_transactions);
+    let mut ready_receivers = Vec::with_capacity(num_transactions);
 
-        let (tx_to_thread, rx_from_main_for_tx) = oneshot::channel::<PreparedTransaction>();
+    for prep_tx in prepared_transactions_input {
         let (tx_from_thread_for_result, rx_for_main_for_result) = oneshot::channel::<SendAttempt>();
+        let (ready_tx, ready_rx) = oneshot::channel::<()>();
+        result_collectors.push((
+            rx_for_main_for_result,
+            prep_tx.signature,
+            prep_tx.endpoint.name.clone(),
+        ));
+        ready_receivers.push(ready_rx);
+        let gate = gate.clone();
+        let runtime = runtime.clone();
+        let thread_name = format!("sender-{}", prep_tx.endpoint.name);
+
+        let spawn_result = std_thread::Builder::new().name(thread_name).spawn(move || {
+            let _runtime_guard = runtime.enter();
+            // Connecting can take seconds, e.g. to look up the TPU leaders, so
+            // it happens before the start gate opens.
+            let send_path = prep_tx.endpoint.send_path.connect(&prep_tx.endpoint);
+            let warm_up = send_path.as_ref().ok().and_then(|path| {
+                let start = Instant::now();
+                path.warm_up()
+                    .map(|result| (start.elapsed(), result.err()))
+            });
+            match &warm_up {
+                Some((rtt, None)) => println!(
+                    "Thread for RPC {}: Connection warmed up in {}ms.",
+                    prep_tx.endpoint.name,
+                    rtt.as_millis()
+                ),
+                Some((rtt, Some(e))) => eprintln!(
+                    "Thread for RPC {}: Warm-up failed after {}ms: {}",
+                    prep_tx.endpoint.name,
+                    rtt.as_millis(),
+                    e
+                ),
+                None => {}
+            }
+            let wire_transaction = WireTransaction::serialize(&prep_tx);
 
-        let rpc_url_for_closure = rpc_url_for_thread_logging.clone();
-
-        let handle = std_thread::spawn(move || {
-            let runtime_result = TokioRuntimeBuilder::new_multi_thread().enable_all().build();
+            let on_time = gate.arrive();
+            let _ = ready_tx.send(());
+            if on_time {
+                println!(
+                    "Thread for RPC {}: Ready with Tx (sig: {}), waiting at the start gate...",
+                    prep_tx.endpoint.name, prep_tx.signature
+                );
+                gate.wait();
+            }
 
-            let runtime = match runtime_result {
-                Ok(rt) => rt,
+            let outcome = match (&send_path, wire_transaction) {
+                _ if !on_time => SendOutcome::failed(format!(
+                    "Not dispatched: not ready within the {}s warm-up timeout",
+                    WARM_UP_TIMEOUT_SECONDS
+                )),
+                (Ok(path), Ok(wire_transaction)) => path.send(&wire_transaction),
+                (Err(e), _) => SendOutcome::failed(e.clone()),
+                (_, Err(e)) => SendOutcome::failed(e),
+            };
+            let duration = outcome.duration;
+
+            let send_result_outcome = match outcome.result {
+                Ok(returned_signature) => {
+                    println!(
+                        "Thread for RPC {}: Successfully sent Tx (original sig: {}). Returned sig: {}. Time: {}ms",
+                        prep_tx.endpoint.name,
+                        prep_tx.signature,
+                        returned_signature,
+                        duration.as_millis()
+                    );
+                    Ok(returned_signature)
+                }
                 Err(e) => {
                     eprintln!(
-                        "Thread for future RPC {}: Failed to create Tokio runtime: {}. Thread will exit.",
-                        rpc_url_for_closure, e
+                        "Thread for RPC {}: Error sending Tx (original sig: {}). Error: {}. Time: {}ms",
+                        prep_tx.endpoint.name,
+                        prep_tx.signature,
+                        e,
+                        duration.as_millis()
                     );
-                    return;
+                    Err(e)
                 }
             };
 
-            runtime.block_on(async {
-                println!(
-                    "Thread for future RPC {}: Started, waiting for transaction...",
-                    rpc_url_for_closure
+            let attempt = SendAttempt {
+                endpoint: prep_tx.endpoint.clone(),
+                original_signature: prep_tx.signature,
+                amount_lamports: prep_tx.amount_lamports,
+                compute_budget: prep_tx.compute_budget,
+                fee_lamports: prep_tx.fee_lamports,
+                send_result: send_result_outcome,
+                bundle_id: outcome.bundle_id,
+                bundle_status: None,
+                rebroadcasts: 0,
+                landed_send: None,
+                warm_up_ms: warm_up.as_ref().map(|(rtt, _)| rtt.as_millis()),
+                warm_up_error: warm_up.and_then(|(_, error)| error),
+                send_options: prep_tx
+                    .endpoint
+                    .send_path
+                    .uses_send_options()
+                    .then(|| prep_tx.endpoint.send_options.effective()),
+                send_start_instant: outcome.start_instant,
+                dispatch_at_write: outcome.dispatch_at_write,
+                submit_start_instant: outcome.submitted_at,
+                send_start_time: outcome.start_time,
+                send_duration_ms: duration.as_millis(),
+                timing: outcome.timing,
+                connection_timing: outcome.connection_timing,
+                position: prep_tx.position,
+                dispatch_slot: None,
+                dispatched: on_time,
+            };
+
+            if tx_from_thread_for_result.send(attempt).is_err() {
+                eprintln!(
+                    "Thread for RPC {}: Failed to send result back to main. Original sig: {}.",
+                    prep_tx.endpoint.name, prep_tx.signature
                 );
-                match rx_from_main_for_tx.await {
-                    Ok(prep_tx) => {
-                        println!(
-                            "Thread for RPC {}: Received Tx (sig: {}), commencing send.",
-                            prep_tx.rpc_url, prep_tx.signature
-                        );
-
-                        let rpc_client = RpcClient::new(prep_tx.rpc_url.clone());
-                        let start_time = Instant::now();
-                        let send_tx_result = rpc_client.send_transaction(&prep_tx.transaction);
-                        let duration = start_time.elapsed();
-
-                        let send_result_outcome = match send_tx_result {
-                            Ok(returned_signature) => {
-                                println!(
-                                    "Thread for RPC {}: Successfully sent Tx (original sig: {}). Returned sig: {}. Time: {}ms",
-                                    prep_tx.rpc_url,
-                                    prep_tx.signature,
-                                    returned_signature,
-                                    duration.as_millis()
-                                );
-                                Ok(returned_signature)
-                            }
-                            Err(e) => {
-                                eprintln!(
-                                    "Thread for RPC {}: Error sending Tx (original sig: {}). Error: {}. Time: {}ms",
-                                    prep_tx.rpc_url,
-                                    prep_tx.signature,
-                                    e,
-                                    duration.as_millis()
-                                );
-                                Err(e.to_string())
-                            }
-                        };
-
-                        let attempt = SendAttempt {
-                            rpc_url: prep_tx.rpc_url.clone(),
-                            original_signature: prep_tx.signature,
-                            amount_lamports: prep_tx.amount_lamports,
-                            send_result: send_result_outcome,
-                            send_start_instant: start_time,
-                            send_duration_ms: duration.as_millis(),
-                        };
-
-                        if tx_from_thread_for_result.send(attempt).is_err() {
-                            eprintln!(
-                                "Thread for RPC {}: Failed to send result back to main. Original sig: {}.",
-                                prep_tx.rpc_url,
-                                prep_tx.signature
-                            );
-                        }
-                    }
-                    Err(_) => {
-                        eprintln!(
-                            "Thread for future RPC {}: Failed to receive transaction from main. Channel closed. Thread will exit.",
-                            rpc_url_for_closure
-                        );
-                    }
-                }
-            });
+            }
         });
-        thread_setups.push((
-            handle,
-            tx_to_thread,
-            rx_for_main_for_result,
-            rpc_url_for_thread_logging,
-        ));
+        match spawn_result {
+            Ok(handle) => handles_to_join.push(handle),
+            // The dropped channels make this attempt count as ready and missing.
+            Err(e) => eprintln!("Main: Failed to spawn a sender thread: {}", e),
+        }
     }
 
     println!(
-        "Phase 1 complete. All {} threads created and waiting.",
-        thread_setups.len()
+        "Phase 1: Waiting for {} threads to connect and warm up...",
+        num_transactions
     );
-    println!("Phase 2: Wait 2 seconds and then dispatching transactions to respective threads...");
-    std_thread::sleep(std::time::Duration::from_secs(2));
-
-    let mut result_collectors = Vec::with_capacity(num_transactions);
-    let mut handles_to_join = Vec::with_capacity(num_transactions);
-
-    for (prep_tx, (handle, sender_to_thread, result_receiver, _thread_rpc_url_for_log)) in
-        prepared_transactions_input
-            .into_iter()
-            .zip(thread_setups.into_iter())
-    {
-        let log_sig_on_dispatch_fail = prep_tx.signature;
-        let log_rpc_on_dispatch_fail = prep_tx.rpc_url.clone();
-
-        if sender_to_thread.send(prep_tx).is_err() {
-            eprintln!(
-                "Main: Failed to dispatch Tx (sig: {}) to thread for RPC {}. The thread will likely error out.",
-                log_sig_on_dispatch_fail, log_rpc_on_dispatch_fail
-            );
-        } else {
-            println!(
-                "Main: Dispatched Tx (sig: {}) to thread for RPC {}.",
-                log_sig_on_dispatch_fail, log_rpc_on_dispatch_fail
-            );
+    // A thread that exits early drops its sender, which counts as ready.
+    let all_ready = async {
+        for ready_rx in ready_receivers {
+            let _ = ready_rx.await;
         }
-        handles_to_join.push(handle);
-        result_collectors.push((
-            result_receiver,
-            log_sig_on_dispatch_fail,
-            log_rpc_on_dispatch_fail,
-        ));
+    };
+    let timed_out = tokio::time::timeout(Duration::from_secs(WARM_UP_TIMEOUT_SECONDS), all_ready)
+        .await
+        .is_err();
+
+    let released = gate.open();
+    if timed_out {
+        eprintln!(
+            "Phase 1: Only {} of {} threads were ready after {} seconds; the others will not be dispatched and are left out of the dispatch skew.",
+            released, num_transactions, WARM_UP_TIMEOUT_SECONDS
+        );
+    } else {
+        println!("Phase 1 complete. All threads were ready at the start gate.");
     }
+    println!(
+        "Phase 2: Start gate opened, {} transaction(s) released at once.",
+        released
+    );
 
-    println!("Phase 2 complete. All transactions dispatched.",);
     println!(
         "Phase 3: Collecting results from {} threads...",
         result_collectors.len()
@@ -287,7 +660,7 @@ pub async fn send_transactions_concurrently(
 
     let mut send_attempts = Vec::with_capacity(result_collectors.len());
 
-    for (receiver, original_sig_for_error, rpc_url_for_error) in result_collectors {
+    for (receiver, original_sig_for_error, endpoint_name_for_error) in result_collectors {
         match receiver.await {
             Ok(attempt) => {
                 send_attempts.push(attempt);
@@ -295,7 +668,7 @@ pub async fn send_transactions_concurrently(
             Err(_) => {
                 eprintln!(
                     "Main: Failed to receive result from thread for Tx (original sig: {}, RPC: {}). Channel closed. Thread may have failed/panicked.",
-                    original_sig_for_error, rpc_url_for_error
+                    original_sig_for_error, endpoint_name_for_error
                 );
             }
         }
@@ -341,12 +714,14 @@ pub async fn simulate_transactions_concurrently(
         let task = tokio::spawn(async move {
             println!(
                 "Preparing to simulate Tx (sig: {}) on RPC: {}",
-                prep_tx.signature, prep_tx.rpc_url
+                prep_tx.signature, prep_tx.endpoint.name
             );
-            let rpc_client = RpcClient::new(prep_tx.rpc_url.clone());
+            let rpc_client = prep_tx
+                .endpoint
+                .nonblocking_rpc_client(CommitmentConfig::default());
             let start_time = Instant::now();
 
-            let result = rpc_client.simulate_transaction(&prep_tx.transaction);
+            let result = rpc_client.simulate_transaction(&prep_tx.transaction).await;
             let duration = start_time.elapsed();
 
             let (simulation_status_output_str, final_sim_result_for_struct) = match result {
@@ -368,22 +743,22 @@ pub async fn simulate_transactions_concurrently(
                     };
                     (output, Ok(sim_value))
                 }
-                Err(e) => (
-                    format!("RPC Error during simulation: {}", e),
-                    Err(e.to_string()),
-                ),
+                Err(e) => {
+                    let e = redact_client_error(e);
+                    (format!("RPC Error during simulation: {}", e), Err(e))
+                }
             };
 
             println!(
                 "Tx (original sig: {}) on RPC {}: {}. Time: {}ms",
                 prep_tx.signature,
-                prep_tx.rpc_url,
+                prep_tx.endpoint.name,
                 simulation_status_output_str,
                 duration.as_millis()
             );
 
             SimulationAttempt {
-                rpc_url: prep_tx.rpc_url,
+                endpoint: prep_tx.endpoint,
                 original_signature: prep_tx.signature,
                 amount_lamports: prep_tx.amount_lamports,
                 simulation_result: final_sim_result_for_struct,
@@ -404,3 +779,172 @@ pub async fn simulate_transactions_concurrently(
     }
     simulation_attempts
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Signer;
+
+    #[test]
+    fn test_start_gate_releases_all_waiting_threads() {
+        let gate = Arc::new(StartGate::new());
+        let waiting = Arc::new(std::sync::Barrier::new(4));
+        let handles: Vec<_> = (0..3)
+            .map(|_| {
+                let gate = gate.clone();
+                let waiting = waiting.clone();
+                std_thread::spawn(move || {
+                    assert!(gate.arrive());
+                    waiting.wait();
+                    gate.wait();
+                    Instant::now()
+                })
+            })
+            .collect();
+
+        waiting.wait();
+        std_thread::sleep(Duration::from_millis(20));
+        let opened_at = Instant::now();
+        assert_eq!(gate.open(), 3);
+
+        for handle in handles {
+            assert!(handle.join().unwrap() >= opened_at);
+        }
+        // A thread arriving after the start missed it.
+        assert!(!gate.arrive());
+    }
+
+    #[test]
+    fn test_mixed_dispatch_reference_ignores_undispatched_attempts() {
+        let endpoint = || Endpoint::from_url("a".to_string(), "http://localhost".to_string());
+        let at_write = SendAttempt {
+            dispatch_at_write: true,
+            ..SendAttempt::accepted(endpoint())
+        };
+        let at_submit = SendAttempt::accepted(endpoint());
+        let missed = SendAttempt {
+            dispatched: false,
+            ..SendAttempt::accepted(endpoint())
+        };
+
+        assert!(!mixed_dispatch_reference(&[
+            at_write.clone(),
+            at_write.clone()
+        ]));
+        assert!(!mixed_dispatch_reference(&[at_write.clone(), missed]));
+        assert!(mixed_dispatch_reference(&[at_write, at_submit]));
+    }
+
+    #[test]
+    fn test_mixed_race_skew_is_measured_between_submission_starts() {
+        let endpoint = || Endpoint::from_url("a".to_string(), "http://localhost".to_string());
+        let started = Instant::now();
+        let late = started + Duration::from_millis(5);
+        // The write 5ms into the first submission coincides with the start of
+        // the second one, which has no write instant.
+        let at_write = SendAttempt {
+            dispatch_at_write: true,
+            send_start_instant: late,
+            submit_start_instant: started,
+            ..SendAttempt::accepted(endpoint())
+        };
+        let at_submit = SendAttempt {
+            send_start_instant: late,
+            submit_start_instant: late,
+            ..SendAttempt::accepted(endpoint())
+        };
+
+        let skew = dispatch_skew(&[at_write, at_submit]);
+
+        assert_eq!(skew, Some(Duration::from_millis(5)));
+        assert!(is_unfair(skew, 1_000));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_sender_threads_share_the_callers_runtime() {
+        let endpoint_count = 32;
+        // Every endpoint warms up with a HEAD request, then posts its transaction.
+        let (url, _requests) = crate::send_path::mock_http::serve(
+            (0..endpoint_count * 2)
+                .map(|_| ("HTTP/1.1 200 OK", "ok".to_string()))
+                .collect(),
+        );
+        let payer = Keypair::new();
+        let prepared: Vec<_> = (0..endpoint_count)
+            .map(|i| {
+                let mut endpoint =
+                    Endpoint::from_url(format!("relay-{}", i), "http://localhost:8899".to_string());
+                endpoint.send_path = SendPathConfig::HttpRelay(crate::send_path::HttpRelayConfig {
+                    url: url.clone(),
+                    headers: Default::default(),
+                    body_template: "{{signature}}".to_string(),
+                });
+                let transaction = TransactionFormat::legacy(Hash::new_unique())
+                    .compile_and_sign(
+                        &payer.pubkey(),
+                        &[system_instruction::transfer(
+                            &payer.pubkey(),
+                            &Pubkey::new_unique(),
+                            1,
+                        )],
+                        &[&payer],
+                    )
+                    .unwrap();
+                PreparedTransaction {
+                    endpoint,
+                    signature: transaction.signatures[0],
+                    transaction,
+                    amount_lamports: 1,
+                    compute_budget: ComputeBudgetConfig::default(),
+                    fee_lamports: 5000,
+                    tip_transaction: None,
+                    last_valid_block_height: None,
+                    position: i,
+                }
+            })
+            .collect();
+
+        let attempts = send_transactions_concurrently(prepared).await;
+
+        assert_eq!(attempts.len(), endpoint_count);
+        for attempt in &attempts {
+            assert_eq!(attempt.send_result, Ok(attempt.original_signature));
+            assert_eq!(attempt.warm_up_error, None);
+        }
+    }
+
+    #[test]
+    fn test_v0_format_loads_accounts_from_lookup_table() {
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        let table_key = Pubkey::new_unique();
+        let format = TransactionFormat {
+            version: TransactionVersion::V0,
+            blockhash: Hash::new_unique(),
+            lookup_tables: vec![AddressLookupTableAccount {
+                key: table_key,
+                addresses: vec![Pubkey::new_unique(), recipient],
+            }],
+        };
+        let instructions = [system_instruction::transfer(&payer.pubkey(), &recipient, 1)];
+
+        let transaction = format
+            .compile_and_sign(&payer.pubkey(), &instructions, &[&payer])
+            .unwrap();
+
+        let lookups = transaction.message.address_table_lookups().unwrap();
+        assert_eq!(lookups.len(), 1);
+        assert_eq!(lookups[0].account_key, table_key);
+        assert_eq!(lookups[0].writable_indexes, vec![1]);
+        assert!(!transaction
+            .message
+            .static_account_keys()
+            .contains(&recipient));
+        assert!(transaction.verify_with_results().iter().all(|ok| *ok));
+
+        let legacy = TransactionFormat::legacy(format.blockhash)
+            .compile_and_sign(&payer.pubkey(), &instructions, &[&payer])
+            .unwrap();
+        assert!(legacy.message.address_table_lookups().is_none());
+    }
+}